@@ -0,0 +1,5 @@
+use gpx_tools::gpx_diff;
+
+fn main() -> anyhow::Result<()> {
+    gpx_diff::cli::run_cli()
+}
@@ -0,0 +1,5 @@
+use gpx_tools::gpx_elevation;
+
+fn main() -> anyhow::Result<()> {
+    gpx_elevation::cli::run_cli()
+}
@@ -0,0 +1,5 @@
+use gpx_tools::gpx_profile;
+
+fn main() -> anyhow::Result<()> {
+    gpx_profile::cli::run_cli()
+}
@@ -0,0 +1,5 @@
+use gpx_tools::gpx_render;
+
+fn main() -> anyhow::Result<()> {
+    gpx_render::cli::run_cli()
+}
@@ -0,0 +1,5 @@
+use gpx_tools::gpx_report;
+
+fn main() -> anyhow::Result<()> {
+    gpx_report::cli::run_cli()
+}
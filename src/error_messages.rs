@@ -7,3 +7,22 @@ pub const OUTPUT_FILE_WRITE_ERROR: &str = "Could not write to output file.";
 pub const OUTPUT_FILE_CREATION_ERROR: &str = "Could not create output file.";
 pub const KML_SERIALIZE_ERROR: &str = "Could not serialize KML file.";
 pub const OUTPUT_PATH_IS_NOT_FOLDER: &str = "Output folder is not a directory.";
+pub const OUTPUT_SERIALIZE_ERROR: &str = "Could not serialize output.";
+pub const STDOUT_WRITE_ERROR: &str = "Could not write to standard output.";
+pub const INPUT_PATH_NOT_FOUND: &str = "Input path does not exist.";
+pub const INVALID_GLOB_PATTERN: &str = "Invalid glob pattern.";
+pub const DIRECTORY_READ_ERROR: &str = "Could not read directory.";
+pub const NO_INPUT_FILES_FOUND: &str = "No input files found.";
+pub const NO_POINTS_TO_RENDER: &str = "The file does not contain any points to render.";
+pub const NAMES_FILE_READ_ERROR: &str = "Could not read names file.";
+pub const DEM_FILE_READ_ERROR: &str = "Could not read elevation model file.";
+pub const NO_DEM_FILES_FOUND: &str = "No elevation model files (.hgt, .tif, .tiff) found.";
+pub const UNSUPPORTED_DEM_FILE: &str = "Unsupported elevation model file. Supported are SRTM tiles (.hgt) and single-band GeoTIFFs \
+    in geographic coordinates (WGS 84).";
+pub const INVALID_HGT_FILE_NAME: &str =
+    "Invalid SRTM file name. Expected the coordinates of the tile, e.g. 'N47E011.hgt'.";
+pub const GEOID_FILE_READ_ERROR: &str = "Could not read geoid grid file.";
+pub const INVALID_GEOID_GRID: &str =
+    "Invalid geoid grid file. Expected an NGA ASCII grid (e.g. WW15MGH.GRD) or a GeoTIFF.";
+pub const TIME_OUT_OF_RANGE: &str = "Shifted time out of range.";
+pub const NO_READABLE_INPUT_FILES: &str = "None of the input files could be read.";
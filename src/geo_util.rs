@@ -0,0 +1,110 @@
+use geo::{Bearing, Coord, Distance, Haversine, Point};
+
+/// Mean earth radius in metres, as used by the Haversine formula.
+pub const EARTH_RADIUS_IN_M: f64 = 6_371_008.8;
+
+/// Equirectangular projection around a reference point. Converts longitude / latitude
+/// to metres east / north of the reference point. Accurate for small areas, such as the
+/// surroundings of a track.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LocalProjection {
+    origin: Point,
+    metres_per_degree_x: f64,
+    metres_per_degree_y: f64,
+}
+
+impl LocalProjection {
+    pub fn new(origin: Point) -> Self {
+        let metres_per_degree_y = EARTH_RADIUS_IN_M.to_radians();
+        LocalProjection {
+            origin,
+            metres_per_degree_x: metres_per_degree_y * origin.y().to_radians().cos(),
+            metres_per_degree_y,
+        }
+    }
+
+    /// Creates a projection centred on the bounding box of the given points.
+    pub fn centred_on(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut min = Coord {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        };
+        let mut max = Coord {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        };
+
+        for point in points {
+            min.x = min.x.min(point.x());
+            min.y = min.y.min(point.y());
+            max.x = max.x.max(point.x());
+            max.y = max.y.max(point.y());
+        }
+
+        if min.x > max.x {
+            return None;
+        }
+
+        Some(LocalProjection::new(Point::new(
+            (min.x + max.x) / 2.0,
+            (min.y + max.y) / 2.0,
+        )))
+    }
+
+    pub fn project(&self, point: Point) -> Coord {
+        Coord {
+            x: (point.x() - self.origin.x()) * self.metres_per_degree_x,
+            y: (point.y() - self.origin.y()) * self.metres_per_degree_y,
+        }
+    }
+
+    pub fn unproject(&self, coord: Coord) -> Point {
+        Point::new(
+            self.origin.x() + coord.x / self.metres_per_degree_x,
+            self.origin.y() + coord.y / self.metres_per_degree_y,
+        )
+    }
+}
+
+/// Returns the position of the point on the segment `a`-`b` that is closest to `p`,
+/// as a fraction between 0 (at `a`) and 1 (at `b`), together with its distance to `p`.
+pub fn closest_point_on_segment(p: Coord, a: Coord, b: Coord) -> (f64, f64) {
+    let ab = b - a;
+    let length_squared = ab.x * ab.x + ab.y * ab.y;
+
+    let t = if length_squared > 0.0 {
+        (((p.x - a.x) * ab.x + (p.y - a.y) * ab.y) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let closest = a + ab * t;
+    (t, distance(p, closest))
+}
+
+pub fn distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+/// Returns the distance (in metres) of `p` from the great-circle segment `a`-`b` on the sphere.
+/// Unlike a projection, this is exact for long segments and across the antimeridian.
+pub fn distance_to_great_circle_segment(p: Point, a: Point, b: Point) -> f64 {
+    let distance_from_a = Haversine.distance(a, p);
+    if Haversine.distance(a, b) == 0.0 {
+        return distance_from_a;
+    }
+
+    let bearing_ab = Haversine.bearing(a, b).to_radians();
+    let bearing_ap = Haversine.bearing(a, p).to_radians();
+    let bearing_ba = Haversine.bearing(b, a).to_radians();
+    let bearing_bp = Haversine.bearing(b, p).to_radians();
+
+    // The closest point is within the segment if `p` is ahead of both ends.
+    if (bearing_ap - bearing_ab).cos() > 0.0 && (bearing_bp - bearing_ba).cos() > 0.0 {
+        let angular_distance = distance_from_a / EARTH_RADIUS_IN_M;
+        let cross_track = (angular_distance.sin() * (bearing_ap - bearing_ab).sin()).asin();
+        cross_track.abs() * EARTH_RADIUS_IN_M
+    } else {
+        distance_from_a.min(Haversine.distance(b, p))
+    }
+}
@@ -1,6 +1,8 @@
 use clap::ValueEnum;
+use geo::{Distance, Haversine, Point};
 use gpx::{Gpx, GpxVersion, Waypoint};
 use std::io::Write;
+use time::OffsetDateTime;
 
 #[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum EncodingOption {
@@ -14,6 +16,9 @@ pub enum AsciiErrorStrategy {
     Ignore,
     /// Converts non-ASCII characters to '?'
     Replace,
+    /// Converts non-ASCII characters in text fields to similar ASCII characters (e.g. 'ß' to
+    /// "ss", 'ü' to 'u'), and any remaining ones to '?'
+    Transliterate,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
@@ -26,6 +31,28 @@ pub enum VersionOption {
     V10,
 }
 
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SmoothingMethod {
+    /// Mean of the elevations within the window.
+    MovingAverage,
+    /// Median of the elevations within the window; robust against single outliers.
+    Median,
+    /// Quadratic least-squares fit over the window; preserves peaks and valleys better.
+    SavitzkyGolay,
+}
+
+pub const DEFAULT_SMOOTHING_WINDOW: f64 = 100.0;
+
+/// Parses the length of the smoothing window, which has to be a finite number greater than 0.
+pub fn parse_smoothing_window(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(window) if window.is_finite() && window > 0.0 => Ok(window),
+        _ => Err(format!(
+            "invalid smoothing window '{text}' (expected a length in metres greater than 0)"
+        )),
+    }
+}
+
 pub fn set_creator(gpx: &mut Gpx, creator: String) {
     gpx.creator = Some(creator);
 }
@@ -164,17 +191,178 @@ pub fn remove_route_elevation(gpx: &mut Gpx) {
     }
 }
 
+pub fn smooth_track_elevation(gpx: &mut Gpx, method: SmoothingMethod, window_in_m: f64) {
+    for points in track_segment_points_mut(gpx) {
+        smooth_elevation(points, method, window_in_m);
+    }
+}
+
+pub fn smooth_route_elevation(gpx: &mut Gpx, method: SmoothingMethod, window_in_m: f64) {
+    for route in gpx.routes.iter_mut() {
+        smooth_elevation(&mut route.points, method, window_in_m);
+    }
+}
+
+/// Smooths the elevations of a polyline. The window is centred on each point and spans
+/// `window_in_m` metres along the line, so that the result does not depend on the recording
+/// interval. Points without an elevation are left as they are.
+pub fn smooth_elevation(points: &mut [Waypoint], method: SmoothingMethod, window_in_m: f64) {
+    let mut distance_in_m = 0.0;
+    let mut samples: Vec<(usize, f64, f64)> = Vec::with_capacity(points.len());
+
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            distance_in_m += Haversine.distance(points[i - 1].point(), point.point());
+        }
+        if let Some(elevation) = point.elevation {
+            samples.push((i, distance_in_m, elevation));
+        }
+    }
+
+    let half_window = window_in_m / 2.0;
+    let mut window_start = 0;
+    let mut window_end = 0;
+
+    for &(index, centre, _) in &samples {
+        while window_start < samples.len() && samples[window_start].1 < centre - half_window {
+            window_start += 1;
+        }
+        while window_end < samples.len() && samples[window_end].1 <= centre + half_window {
+            window_end += 1;
+        }
+
+        let window = &samples[window_start..window_end.max(window_start)];
+        if window.is_empty() {
+            continue;
+        }
+        let elevations = window.iter().map(|&(_, _, elevation)| elevation);
+
+        let smoothed = match method {
+            SmoothingMethod::MovingAverage => elevations.sum::<f64>() / window.len() as f64,
+            SmoothingMethod::Median => {
+                let mut sorted: Vec<f64> = elevations.collect();
+                sorted.sort_by(f64::total_cmp);
+                let middle = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[middle - 1] + sorted[middle]) / 2.0
+                } else {
+                    sorted[middle]
+                }
+            }
+            SmoothingMethod::SavitzkyGolay => fit_quadratic_at_centre(window, centre, half_window)
+                .unwrap_or_else(|| elevations.sum::<f64>() / window.len() as f64),
+        };
+
+        points[index].elevation = Some(smoothed);
+    }
+}
+
+/// Fits a quadratic polynomial to the (distance, elevation) samples by least squares and
+/// returns its value at the centre. This is the Savitzky-Golay filter generalised to unevenly
+/// spaced points. Returns `None` if the fit is underdetermined.
+fn fit_quadratic_at_centre(window: &[(usize, f64, f64)], centre: f64, scale: f64) -> Option<f64> {
+    if window.len() < 3 || scale <= 0.0 {
+        return None;
+    }
+
+    // Sums of x^k (for k = 0..4) and x^k * y (for k = 0..2), with x scaled to about -1..1.
+    let mut x_sums = [0.0; 5];
+    let mut xy_sums = [0.0; 3];
+
+    for &(_, distance, elevation) in window {
+        let x = (distance - centre) / scale;
+        let mut x_power = 1.0;
+        for k in 0..5 {
+            x_sums[k] += x_power;
+            if k < 3 {
+                xy_sums[k] += x_power * elevation;
+            }
+            x_power *= x;
+        }
+    }
+
+    let [s0, s1, s2, s3, s4] = x_sums;
+    let [t0, t1, t2] = xy_sums;
+
+    // Solve the normal equations for the constant term with Cramer's rule.
+    let determinant =
+        s0 * (s2 * s4 - s3 * s3) - s1 * (s1 * s4 - s3 * s2) + s2 * (s1 * s3 - s2 * s2);
+    if determinant.abs() < 1e-9 * s0.powi(3) {
+        return None;
+    }
+
+    let constant_determinant =
+        t0 * (s2 * s4 - s3 * s3) - s1 * (t1 * s4 - s3 * t2) + s2 * (t1 * s3 - s2 * t2);
+
+    Some(constant_determinant / determinant)
+}
+
 pub fn remove_non_ascii_chars(text: &mut Vec<u8>, strategy: AsciiErrorStrategy) {
     match strategy {
         AsciiErrorStrategy::Ignore => {
             text.retain(|c| c.is_ascii());
         }
-        AsciiErrorStrategy::Replace => {
-            for char in text.iter_mut() {
-                if !char.is_ascii() {
-                    *char = b'?';
-                }
-            }
+        AsciiErrorStrategy::Replace | AsciiErrorStrategy::Transliterate => {
+            // Replace each character rather than each byte of multi-byte characters.
+            let replaced: String = String::from_utf8_lossy(text)
+                .chars()
+                .map(|c| if c.is_ascii() { c } else { '?' })
+                .collect();
+            *text = replaced.into_bytes();
         }
     }
 }
+
+/// Returns the points of each track segment.
+pub fn track_segment_points_mut(gpx: &mut Gpx) -> impl Iterator<Item = &mut Vec<Waypoint>> {
+    gpx.tracks
+        .iter_mut()
+        .flat_map(|track| track.segments.iter_mut())
+        .map(|segment| &mut segment.points)
+}
+
+/// Returns the points of each track segment and each route.
+pub fn polyline_points_mut(gpx: &mut Gpx) -> impl Iterator<Item = &mut Vec<Waypoint>> {
+    let routes = gpx.routes.iter_mut().map(|route| &mut route.points);
+    let segments = gpx
+        .tracks
+        .iter_mut()
+        .flat_map(|track| track.segments.iter_mut())
+        .map(|segment| &mut segment.points);
+    segments.chain(routes)
+}
+
+/// Returns a copy of the waypoint at a different position. The position of a waypoint cannot
+/// be changed in place.
+pub fn move_waypoint(waypoint: &Waypoint, point: Point) -> Waypoint {
+    let waypoint = waypoint.clone();
+    let mut moved = Waypoint::new(point);
+
+    moved.elevation = waypoint.elevation;
+    moved.speed = waypoint.speed;
+    moved.time = waypoint.time;
+    moved.name = waypoint.name;
+    moved.comment = waypoint.comment;
+    moved.description = waypoint.description;
+    moved.source = waypoint.source;
+    moved.links = waypoint.links;
+    moved.symbol = waypoint.symbol;
+    moved.type_ = waypoint.type_;
+    moved.geoidheight = waypoint.geoidheight;
+    moved.fix = waypoint.fix;
+    moved.sat = waypoint.sat;
+    moved.hdop = waypoint.hdop;
+    moved.vdop = waypoint.vdop;
+    moved.pdop = waypoint.pdop;
+    moved.dgps_age = waypoint.dgps_age;
+    moved.dgpsid = waypoint.dgpsid;
+
+    moved
+}
+
+/// Returns the timestamp of the point as Unix time in seconds.
+pub fn time_in_s(point: &Waypoint) -> Option<f64> {
+    point
+        .time
+        .map(|time| OffsetDateTime::from(time).unix_timestamp_nanos() as f64 / 1e9)
+}
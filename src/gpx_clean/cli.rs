@@ -1,10 +1,27 @@
 use super::cleaner::*;
+use super::duplicates;
+use super::metadata;
+use super::metadata::MetadataUpdate;
+use super::outliers;
+use super::pauses;
+use super::pauses::{DEFAULT_PAUSE_DURATION, DEFAULT_PAUSE_RADIUS, PauseAction, PauseConfig};
+use super::precision;
+use super::precision::PrecisionConfig;
+use super::privacy;
+use super::privacy::{DEFAULT_PRIVACY_RADIUS, PrivacyZone};
+use super::renaming;
+use super::renaming::{NameSource, NameTemplate, RenameTarget};
+use super::times;
+use super::times::DEFAULT_ANONYMISED_START;
+use super::transliteration;
 use crate::{error_messages, gpx_cli_util, util};
 use anyhow::Context;
 use clap::Parser;
+use gpx::Link;
 use log::info;
 use std::fs;
 use std::path::PathBuf;
+use time::{Duration, OffsetDateTime};
 
 #[derive(Parser)]
 pub struct Args {
@@ -35,10 +52,72 @@ pub struct Args {
     #[arg(long = "set-version", default_value = "1.1")]
     set_version: VersionOption,
 
+    /// Set the name of the GPX file in the metadata.
+    #[arg(long = "set-name")]
+    set_name: Option<String>,
+
+    /// Set the description of the GPX file in the metadata.
+    #[arg(long = "set-description")]
+    set_description: Option<String>,
+
+    /// Set the name of the author in the metadata.
+    #[arg(long = "set-author-name")]
+    set_author_name: Option<String>,
+
+    /// Set the email address of the author in the metadata.
+    #[arg(long = "set-author-email", value_parser = metadata::parse_email)]
+    set_author_email: Option<String>,
+
+    /// Set the link (URL) to the author's website in the metadata.
+    #[arg(long = "set-author-link")]
+    set_author_link: Option<String>,
+
+    /// Set the copyright holder in the metadata.
+    #[arg(long = "set-copyright-author")]
+    set_copyright_author: Option<String>,
+
+    /// Set the copyright year in the metadata.
+    #[arg(long = "set-copyright-year")]
+    set_copyright_year: Option<i32>,
+
+    /// Set the license (e.g. a URL) in the metadata.
+    #[arg(long = "set-copyright-license")]
+    set_copyright_license: Option<String>,
+
+    /// Set the keywords in the metadata.
+    #[arg(long = "set-keywords")]
+    set_keywords: Option<String>,
+
+    /// Add a link to the metadata, given as "URL" or "URL text". Can be specified multiple times.
+    #[arg(long = "add-link", value_parser = metadata::parse_link)]
+    add_links: Vec<Link>,
+
+    /// Set the time in the metadata, in RFC 3339 format (e.g. 2024-06-01T08:00:00Z) or "now".
+    #[arg(long = "set-time", value_parser = metadata::parse_time)]
+    set_time: Option<OffsetDateTime>,
+
+    /// Set the bounds in the metadata to the bounding box of all points.
+    #[arg(long = "set-bounds")]
+    set_bounds: bool,
+
     /// Interactively rename each track.
     #[arg(long = "rename-tracks")]
     rename_tracks: bool,
 
+    /// Rename items using a template. Placeholders: {file}, {index}, {date}, {distance_km},
+    /// and {original}.
+    #[arg(long = "name-template", value_parser = renaming::parse_name_template)]
+    name_template: Option<NameTemplate>,
+
+    /// Rename items using a names file: either a CSV file mapping original names to new names,
+    /// or a text file with one new name per line (empty lines keep the original name).
+    #[arg(long = "names-file", conflicts_with = "name_template")]
+    names_file: Option<PathBuf>,
+
+    /// Kinds of items renamed by the name template or names file.
+    #[arg(long = "rename", value_delimiter = ',', default_value = "tracks")]
+    rename_targets: Vec<RenameTarget>,
+
     /// Remove all waypoints.
     #[arg(long = "remove-waypoints")]
     remove_waypoints: bool,
@@ -78,6 +157,107 @@ pub struct Args {
     /// Remove the elevation data from each route point.
     #[arg(long = "remove-route-elevation")]
     remove_route_elevation: bool,
+
+    /// Shift all timestamps by an offset, e.g. "+2h", "-1h30m", "90s", or "-05:00".
+    #[arg(long = "time-shift", value_parser = times::parse_time_offset, allow_hyphen_values = true)]
+    time_shift: Option<Duration>,
+
+    /// Shift all timestamps so that the earliest point time is at this instant (RFC 3339).
+    #[arg(long = "start-time", value_parser = metadata::parse_time, conflicts_with = "time_shift")]
+    start_time: Option<OffsetDateTime>,
+
+    /// Hide the real date and time by shifting all timestamps, so that the recording starts at
+    /// the given instant (RFC 3339). The intervals between the points are preserved.
+    #[arg(
+        long = "anonymise-times",
+        value_parser = metadata::parse_time,
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_ANONYMISED_START,
+        conflicts_with_all = ["time_shift", "start_time"]
+    )]
+    anonymise_times: Option<OffsetDateTime>,
+
+    /// Remove all timestamps, including the time in the metadata.
+    #[arg(long = "remove-times", conflicts_with = "anonymise_times")]
+    remove_times: bool,
+
+    /// Round latitude and longitude to this number of decimals (5 decimals are about 1 m).
+    #[arg(long = "coordinate-precision")]
+    coordinate_precision: Option<u32>,
+
+    /// Round elevations to this number of decimals.
+    #[arg(long = "elevation-precision")]
+    elevation_precision: Option<u32>,
+
+    /// Remove consecutive track and route points within this distance (in metres) of each other.
+    /// Without a value, only points at exactly the same position are removed.
+    #[arg(long = "remove-duplicates", num_args = 0..=1, default_missing_value = "0")]
+    remove_duplicates: Option<f64>,
+
+    /// Remove empty track segments, tracks without points, and routes with fewer than 2 points.
+    #[arg(long = "remove-empty")]
+    remove_empty: bool,
+
+    /// Remove track points that imply a speed (in m/s) above this value from the previous point.
+    #[arg(long = "max-speed")]
+    max_speed: Option<f64>,
+
+    /// Remove single points that jump more than this distance (in metres) off the line and back.
+    #[arg(long = "max-jump")]
+    max_jump: Option<f64>,
+
+    /// Smooth out elevation spikes that rise and fall steeper than this gradient (in percent).
+    #[arg(long = "max-gradient")]
+    max_gradient: Option<f64>,
+
+    /// Smooth the elevation of each track and route to reduce noise that inflates the ascent.
+    #[arg(long = "smooth-elevation")]
+    smooth_elevation: Option<SmoothingMethod>,
+
+    /// Length (in metres along the line) of the window used for smoothing the elevation.
+    #[arg(
+        long = "smoothing-window",
+        default_value_t = DEFAULT_SMOOTHING_WINDOW,
+        value_parser = parse_smoothing_window
+    )]
+    smoothing_window: f64,
+
+    /// Collapse or remove pauses in tracks, where the device stayed within a small radius.
+    #[arg(long = "pauses")]
+    pauses: Option<PauseAction>,
+
+    /// Radius (in metres) within which the points of a pause must stay.
+    #[arg(long = "pause-radius", default_value_t = DEFAULT_PAUSE_RADIUS)]
+    pause_radius: f64,
+
+    /// Minimum duration (in seconds) of a pause.
+    #[arg(long = "pause-duration", default_value_t = DEFAULT_PAUSE_DURATION)]
+    pause_duration: f64,
+
+    /// Split the track segments at each pause.
+    #[arg(long = "split-at-pauses", requires = "pauses")]
+    split_at_pauses: bool,
+
+    /// Remove all points within a privacy zone, given as "lat,lon,radius" (radius in metres).
+    /// Can be specified multiple times.
+    #[arg(long = "privacy-zone", value_parser = privacy::parse_privacy_zone)]
+    privacy_zones: Vec<PrivacyZone>,
+
+    /// GPX file whose waypoints are the centres of privacy zones.
+    #[arg(long = "privacy-zones-file")]
+    privacy_zones_file: Option<PathBuf>,
+
+    /// Radius (in metres) of the privacy zones from the privacy zones file.
+    #[arg(long = "privacy-radius", default_value_t = DEFAULT_PRIVACY_RADIUS)]
+    privacy_radius: f64,
+
+    /// Remove the first part of each track, up to this distance (in metres).
+    #[arg(long = "trim-start")]
+    trim_start: Option<f64>,
+
+    /// Remove the last part of each track, up to this distance (in metres).
+    #[arg(long = "trim-end")]
+    trim_end: Option<f64>,
 }
 
 pub fn run_cli() -> anyhow::Result<()> {
@@ -93,6 +273,31 @@ pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
 
     let mut gpx = gpx_cli_util::read_input_gpx_file(&input_path)?;
 
+    let name_source = if let Some(template) = args.name_template {
+        let file_name = input_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Some(NameSource::Template {
+            template,
+            file_name,
+        })
+    } else if let Some(names_path) = &args.names_file {
+        Some(renaming::read_names_file(names_path)?)
+    } else {
+        None
+    };
+
+    let mut privacy_zones = args.privacy_zones;
+    if let Some(zones_path) = &args.privacy_zones_file {
+        info!("Loading privacy zones...");
+        let zones_gpx = gpx_cli_util::read_input_gpx_file(zones_path)?;
+        privacy_zones.extend(privacy::privacy_zones_from_waypoints(
+            &zones_gpx.waypoints,
+            args.privacy_radius,
+        ));
+    }
+
     info!("Processing...");
     set_version(&mut gpx, args.set_version);
 
@@ -101,6 +306,24 @@ pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
     }
 
     if args.remove_metadata { remove_metadata(&mut gpx); }
+
+    let metadata_update = MetadataUpdate {
+        name: args.set_name,
+        description: args.set_description,
+        author_name: args.set_author_name,
+        author_email: args.set_author_email,
+        author_link: args.set_author_link,
+        copyright_author: args.set_copyright_author,
+        copyright_year: args.set_copyright_year,
+        copyright_license: args.set_copyright_license,
+        keywords: args.set_keywords,
+        links: args.add_links,
+        time: args.set_time,
+    };
+    if !metadata_update.is_empty() {
+        metadata::set_metadata(&mut gpx, &metadata_update);
+    }
+
     if args.remove_waypoints { remove_waypoints(&mut gpx); }
     if args.remove_tracks { remove_tracks(&mut gpx); }
     if args.remove_routes { remove_routes(&mut gpx); }
@@ -111,13 +334,116 @@ pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
     if args.remove_track_elevation { remove_track_elevation(&mut gpx); }
     if args.remove_route_elevation { remove_route_elevation(&mut gpx); }
 
+    if let Some(max_speed) = args.max_speed {
+        let count = outliers::remove_speed_outliers(&mut gpx, max_speed);
+        info!("Removed {count} points exceeding the maximum speed.");
+    }
+
+    if let Some(max_jump) = args.max_jump {
+        let count = outliers::remove_jump_outliers(&mut gpx, max_jump);
+        info!("Removed {count} points jumping off the line.");
+    }
+
+    if let Some(max_gradient) = args.max_gradient {
+        let count = outliers::fix_elevation_spikes(&mut gpx, max_gradient);
+        info!("Corrected the elevation of {count} points.");
+    }
+
+    if let Some(method) = args.smooth_elevation {
+        info!("Smoothing elevation...");
+        smooth_track_elevation(&mut gpx, method, args.smoothing_window);
+        smooth_route_elevation(&mut gpx, method, args.smoothing_window);
+    }
+
+    if let Some(action) = args.pauses {
+        let config = PauseConfig {
+            radius_in_m: args.pause_radius,
+            min_duration_in_s: args.pause_duration,
+            action,
+            split_segments: args.split_at_pauses,
+        };
+        let count = pauses::remove_pauses(&mut gpx, &config);
+        info!("Found {count} pauses.");
+    }
+
+    if !privacy_zones.is_empty() {
+        let count = privacy::remove_points_in_zones(&mut gpx, &privacy_zones);
+        info!("Removed {count} points within privacy zones.");
+    }
+
+    if args.trim_start.is_some() || args.trim_end.is_some() {
+        let count = privacy::trim_tracks(
+            &mut gpx,
+            args.trim_start.unwrap_or(0.0),
+            args.trim_end.unwrap_or(0.0),
+        );
+        info!("Removed {count} points at the start and end of tracks.");
+    }
+
+    if let Some(offset) = args.time_shift {
+        let count = times::shift_times(&mut gpx, offset)?;
+        info!("Shifted {count} timestamps.");
+    }
+
+    if let Some(start_time) = args.start_time {
+        let count = times::shift_times_to_start(&mut gpx, start_time)?;
+        info!("Shifted {count} timestamps.");
+    }
+
+    if let Some(start) = args.anonymise_times {
+        let count = times::anonymise_times(&mut gpx, start)?;
+        info!("Anonymised {count} timestamps.");
+    }
+
+    if args.remove_times {
+        let count = times::remove_times(&mut gpx);
+        info!("Removed {count} timestamps.");
+    }
+
+    let precision = PrecisionConfig {
+        coordinate_decimals: args.coordinate_precision,
+        elevation_decimals: args.elevation_precision,
+    };
+    if precision.is_enabled() {
+        let count = precision::reduce_precision(&mut gpx, &precision);
+        info!("Removed {count} duplicate points after rounding.");
+    }
+
+    if let Some(min_distance) = args.remove_duplicates {
+        let count = duplicates::remove_duplicate_points(&mut gpx, min_distance);
+        info!("Removed {count} duplicate points.");
+    }
+
+    if args.remove_empty {
+        let count = duplicates::remove_empty_containers(&mut gpx);
+        info!("Removed {count} empty track segments, tracks, and routes.");
+    }
+
+    if let Some(name_source) = &name_source {
+        let count = renaming::rename_items(&mut gpx, name_source, &args.rename_targets);
+        info!("Renamed {count} items.");
+    }
+
     if args.rename_tracks { rename_tracks_interactively(&mut gpx); }
 
+    // The bounds are computed last, so that they reflect all removed points.
+    if args.set_bounds && !metadata::set_bounds(&mut gpx) {
+        info!("No points found, the bounds were not set.");
+    }
+
+    let is_ascii_output = args.encoding == EncodingOption::Ascii;
+
+    if is_ascii_output && args.strategy == AsciiErrorStrategy::Transliterate {
+        info!("Transliterating text...");
+        transliteration::transliterate_text_fields(&mut gpx);
+    }
+
     info!("Serializing GPX file...");
     let mut output = Vec::new();
     gpx::write(&gpx, &mut output).with_context(|| error_messages::GPX_SERIALIZE_ERROR)?;
+    metadata::insert_copyright(&gpx, &mut output);
 
-    if args.encoding == EncodingOption::Ascii {
+    if is_ascii_output {
         info!("Converting to ASCII...");
         remove_non_ascii_chars(&mut output, args.strategy);
     }
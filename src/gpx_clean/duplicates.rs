@@ -0,0 +1,51 @@
+use super::cleaner::polyline_points_mut;
+use geo::{Distance, Haversine};
+use gpx::{Gpx, Waypoint};
+
+/// Removes track and route points that are within `min_distance_in_m` of the previous kept
+/// point. A distance of 0 only removes points at exactly the same position. Returns the number
+/// of removed points.
+pub fn remove_duplicate_points(gpx: &mut Gpx, min_distance_in_m: f64) -> usize {
+    let mut removed_count = 0;
+
+    for points in polyline_points_mut(gpx) {
+        let original_count = points.len();
+        let mut last_kept: Option<Waypoint> = None;
+
+        points.retain(|point| {
+            if let Some(previous) = &last_kept
+                && Haversine.distance(previous.point(), point.point()) <= min_distance_in_m
+            {
+                return false;
+            }
+            last_kept = Some(point.clone());
+            true
+        });
+
+        removed_count += original_count - points.len();
+    }
+
+    removed_count
+}
+
+/// Removes empty track segments, tracks without points, and routes with fewer than two points.
+/// Returns the number of removed segments, tracks, and routes.
+pub fn remove_empty_containers(gpx: &mut Gpx) -> usize {
+    let mut removed_count = 0;
+
+    for track in gpx.tracks.iter_mut() {
+        let original_count = track.segments.len();
+        track.segments.retain(|segment| !segment.points.is_empty());
+        removed_count += original_count - track.segments.len();
+    }
+
+    let original_count = gpx.tracks.len();
+    gpx.tracks.retain(|track| !track.segments.is_empty());
+    removed_count += original_count - gpx.tracks.len();
+
+    let original_count = gpx.routes.len();
+    gpx.routes.retain(|route| route.points.len() >= 2);
+    removed_count += original_count - gpx.routes.len();
+
+    removed_count
+}
@@ -0,0 +1,240 @@
+use crate::svg_util::escape_xml;
+use geo::{Rect, coord};
+use gpx::{Gpx, GpxCopyright, GpxVersion, Link, Metadata, Person, Time};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Metadata fields to set. Fields that are `None` are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetadataUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+    pub author_link: Option<String>,
+    pub copyright_author: Option<String>,
+    pub copyright_year: Option<i32>,
+    pub copyright_license: Option<String>,
+    pub keywords: Option<String>,
+    /// Links to add to the existing ones.
+    pub links: Vec<Link>,
+    pub time: Option<OffsetDateTime>,
+}
+
+impl MetadataUpdate {
+    pub fn is_empty(&self) -> bool {
+        *self == MetadataUpdate::default()
+    }
+}
+
+/// Parses a link in the format "URL" or "URL text".
+pub fn parse_link(text: &str) -> Result<Link, String> {
+    let (href, link_text) = match text.trim().split_once(' ') {
+        Some((href, link_text)) => (href, Some(link_text.trim().to_string())),
+        None => (text.trim(), None),
+    };
+
+    if href.is_empty() {
+        return Err("link URL must not be empty".to_string());
+    }
+
+    Ok(Link {
+        href: href.to_string(),
+        text: link_text,
+        ..Default::default()
+    })
+}
+
+/// Parses an email address. GPX stores it as ID and domain, so it needs exactly one '@'.
+pub fn parse_email(text: &str) -> Result<String, String> {
+    let email = text.trim();
+    match email.split_once('@') {
+        Some((id, domain)) if !id.is_empty() && !domain.is_empty() && !domain.contains('@') => {
+            Ok(email.to_string())
+        }
+        _ => Err(format!(
+            "invalid email address '{text}' (expected e.g. alice@example.com)"
+        )),
+    }
+}
+
+/// Parses a time in RFC 3339 format (e.g. "2024-06-01T08:00:00Z") or the keyword "now".
+pub fn parse_time(text: &str) -> Result<OffsetDateTime, String> {
+    if text.eq_ignore_ascii_case("now") {
+        return Ok(OffsetDateTime::now_utc());
+    }
+
+    OffsetDateTime::parse(text, &Rfc3339).map_err(|_| {
+        format!("invalid time '{text}' (expected RFC 3339, e.g. 2024-06-01T08:00:00Z, or 'now')")
+    })
+}
+
+/// Sets the given metadata fields, creating the metadata, author, and copyright if needed.
+pub fn set_metadata(gpx: &mut Gpx, update: &MetadataUpdate) {
+    let metadata = gpx.metadata.get_or_insert_with(Metadata::default);
+
+    set_if_some(&mut metadata.name, &update.name);
+    set_if_some(&mut metadata.description, &update.description);
+    set_if_some(&mut metadata.keywords, &update.keywords);
+    metadata.links.extend(update.links.iter().cloned());
+
+    if let Some(time) = update.time {
+        metadata.time = Some(Time::from(time));
+    }
+
+    if update.author_name.is_some() || update.author_email.is_some() || update.author_link.is_some()
+    {
+        let author = metadata.author.get_or_insert_with(Person::default);
+        set_if_some(&mut author.name, &update.author_name);
+        set_if_some(&mut author.email, &update.author_email);
+        if let Some(href) = &update.author_link {
+            author.link = Some(Link {
+                href: href.clone(),
+                ..Default::default()
+            });
+        }
+    }
+
+    if update.copyright_author.is_some()
+        || update.copyright_year.is_some()
+        || update.copyright_license.is_some()
+    {
+        let copyright = metadata.copyright.get_or_insert_with(GpxCopyright::default);
+        set_if_some(&mut copyright.author, &update.copyright_author);
+        set_if_some(&mut copyright.license, &update.copyright_license);
+        if update.copyright_year.is_some() {
+            copyright.year = update.copyright_year;
+        }
+    }
+}
+
+/// Sets the metadata bounds to the bounding box of all waypoints, track points, and route points.
+/// Returns `false` if the file does not contain any points.
+pub fn set_bounds(gpx: &mut Gpx) -> bool {
+    let track_points = gpx
+        .tracks
+        .iter()
+        .flat_map(|track| &track.segments)
+        .flat_map(|segment| &segment.points);
+    let route_points = gpx.routes.iter().flat_map(|route| &route.points);
+
+    let bounds = gpx
+        .waypoints
+        .iter()
+        .chain(track_points)
+        .chain(route_points)
+        .map(|point| point.point())
+        .fold(None, |bounds: Option<(f64, f64, f64, f64)>, point| {
+            let (x, y) = (point.x(), point.y());
+            Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+                None => (x, y, x, y),
+            })
+        });
+
+    let Some((min_x, min_y, max_x, max_y)) = bounds else {
+        return false;
+    };
+
+    gpx.metadata.get_or_insert_with(Metadata::default).bounds = Some(Rect::new(
+        coord! { x: min_x, y: min_y },
+        coord! { x: max_x, y: max_y },
+    ));
+
+    true
+}
+
+/// Inserts the copyright into the serialized GPX file, as the `gpx` crate does not write it.
+/// Only GPX 1.1 supports a copyright element.
+pub fn insert_copyright(gpx: &Gpx, output: &mut Vec<u8>) {
+    let Some(metadata) = &gpx.metadata else {
+        return;
+    };
+    let Some(copyright) = &metadata.copyright else {
+        return;
+    };
+    if gpx.version != GpxVersion::Gpx11 {
+        return;
+    }
+
+    let Ok(text) = std::str::from_utf8(output) else {
+        return;
+    };
+
+    // The author attribute is required, so fall back to the name of the file's author.
+    let author = copyright
+        .author
+        .as_deref()
+        .or(metadata
+            .author
+            .as_ref()
+            .and_then(|author| author.name.as_deref()))
+        .unwrap_or_default();
+
+    let mut children = String::new();
+    if let Some(year) = copyright.year {
+        children.push_str(&format!("\n      <year>{year}</year>"));
+    }
+    if let Some(license) = &copyright.license {
+        children.push_str(&format!(
+            "\n      <license>{}</license>",
+            escape_xml(license)
+        ));
+    }
+
+    let element = if children.is_empty() {
+        format!("\n    <copyright author=\"{}\" />", escape_xml(author))
+    } else {
+        format!(
+            "\n    <copyright author=\"{}\">{children}\n    </copyright>",
+            escape_xml(author)
+        )
+    };
+
+    // The metadata is the first element in the file. Any matches further down, e.g. in the
+    // extensions of a track, must not be touched.
+    let Some(metadata_start) = text.find("<metadata") else {
+        return;
+    };
+    let metadata_tail = &text[metadata_start..];
+
+    // Metadata without any other fields is written as an empty element.
+    if metadata_tail.starts_with("<metadata />") {
+        let replacement = format!("<metadata>{element}\n  </metadata>");
+        output.splice(
+            metadata_start..metadata_start + "<metadata />".len(),
+            replacement.into_bytes(),
+        );
+        return;
+    }
+
+    let Some(metadata_end) = metadata_tail
+        .find("</metadata>")
+        .map(|length| metadata_start + length)
+    else {
+        return;
+    };
+
+    // The copyright follows the author in the GPX 1.1 schema. Without an author, it goes before
+    // the first element that follows the copyright.
+    let metadata_text = &text[metadata_start..metadata_end];
+    let insert_at = match metadata_text.find("</author>") {
+        Some(author_end) => metadata_start + author_end + "</author>".len(),
+        None => ["<link", "<time>", "<keywords>", "<bounds"]
+            .iter()
+            .filter_map(|tag| metadata_text.find(tag))
+            .min()
+            .map(|position| metadata_start + metadata_text[..position].trim_end().len())
+            .unwrap_or(metadata_start + metadata_text.trim_end().len()),
+    };
+
+    output.splice(insert_at..insert_at, element.into_bytes());
+}
+
+fn set_if_some(field: &mut Option<String>, value: &Option<String>) {
+    if value.is_some() {
+        field.clone_from(value);
+    }
+}
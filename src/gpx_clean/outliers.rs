@@ -0,0 +1,136 @@
+use super::cleaner::{polyline_points_mut, time_in_s, track_segment_points_mut};
+use geo::{Distance, Haversine};
+use gpx::{Gpx, Waypoint};
+
+/// Distance below which the gradient between two points is not evaluated, as the elevation
+/// noise of the GPS dominates over such short distances.
+const MIN_GRADIENT_DISTANCE_IN_M: f64 = 5.0;
+
+/// Removes track points that could only be reached from the previous point at a speed above
+/// `max_speed_in_mps`. Points without a timestamp are kept. Returns the number of removed points.
+pub fn remove_speed_outliers(gpx: &mut Gpx, max_speed_in_mps: f64) -> usize {
+    let mut removed_count = 0;
+
+    for points in track_segment_points_mut(gpx) {
+        let implied_speed = |a: &Waypoint, b: &Waypoint| -> Option<f64> {
+            let duration_in_s = (time_in_s(b)? - time_in_s(a)?).abs();
+            let distance_in_m = Haversine.distance(a.point(), b.point());
+
+            if duration_in_s > 0.0 {
+                Some(distance_in_m / duration_in_s)
+            } else if distance_in_m > 0.0 {
+                Some(f64::INFINITY)
+            } else {
+                Some(0.0)
+            }
+        };
+        let is_too_fast =
+            |a: &Waypoint, b: &Waypoint| implied_speed(a, b).is_some_and(|s| s > max_speed_in_mps);
+
+        // The first point cannot be compared to a previous one. Drop it if it is an outlier
+        // relative to the following points, which agree with each other.
+        if points.len() >= 3
+            && is_too_fast(&points[0], &points[1])
+            && !is_too_fast(&points[1], &points[2])
+        {
+            points.remove(0);
+            removed_count += 1;
+        }
+
+        let original_count = points.len();
+        let mut last_kept: Option<Waypoint> = None;
+
+        points.retain(|point| {
+            if let Some(previous) = &last_kept
+                && is_too_fast(previous, point)
+            {
+                return false;
+            }
+            last_kept = Some(point.clone());
+            true
+        });
+
+        removed_count += original_count - points.len();
+    }
+
+    removed_count
+}
+
+/// Removes single points that jump more than `max_jump_in_m` away from the line between
+/// their neighbours and back. Returns the number of removed points.
+pub fn remove_jump_outliers(gpx: &mut Gpx, max_jump_in_m: f64) -> usize {
+    let mut removed_count = 0;
+
+    for points in polyline_points_mut(gpx) {
+        let mut i = 1;
+
+        while i + 1 < points.len() {
+            let previous = points[i - 1].point();
+            let point = points[i].point();
+            let next = points[i + 1].point();
+
+            let distance_out = Haversine.distance(previous, point);
+            let distance_back = Haversine.distance(point, next);
+            let direct_distance = Haversine.distance(previous, next);
+
+            // The point is far from both neighbours, which are close to each other.
+            let is_jump = distance_out.min(distance_back) > max_jump_in_m
+                && direct_distance < distance_out.min(distance_back);
+
+            if is_jump {
+                points.remove(i);
+                removed_count += 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    removed_count
+}
+
+/// Replaces the elevation of points that form a spike (a steep rise followed by a steep drop,
+/// or vice versa) with the elevation interpolated between their neighbours. A change is steep
+/// if its gradient exceeds `max_gradient_percent`. Returns the number of corrected points.
+pub fn fix_elevation_spikes(gpx: &mut Gpx, max_gradient_percent: f64) -> usize {
+    let mut fixed_count = 0;
+
+    for points in polyline_points_mut(gpx) {
+        for i in 1..points.len().saturating_sub(1) {
+            let (previous, point, next) = (&points[i - 1], &points[i], &points[i + 1]);
+            let (Some(previous_elevation), Some(elevation), Some(next_elevation)) =
+                (previous.elevation, point.elevation, next.elevation)
+            else {
+                continue;
+            };
+
+            let distance_before = Haversine.distance(previous.point(), point.point());
+            let distance_after = Haversine.distance(point.point(), next.point());
+
+            let gradient_before = (elevation - previous_elevation)
+                / distance_before.max(MIN_GRADIENT_DISTANCE_IN_M)
+                * 100.0;
+            let gradient_after = (next_elevation - elevation)
+                / distance_after.max(MIN_GRADIENT_DISTANCE_IN_M)
+                * 100.0;
+
+            let is_spike = gradient_before.abs() > max_gradient_percent
+                && gradient_after.abs() > max_gradient_percent
+                && gradient_before.signum() != gradient_after.signum();
+
+            if is_spike {
+                let total_distance = distance_before + distance_after;
+                let t = if total_distance > 0.0 {
+                    distance_before / total_distance
+                } else {
+                    0.5
+                };
+                points[i].elevation =
+                    Some(previous_elevation + (next_elevation - previous_elevation) * t);
+                fixed_count += 1;
+            }
+        }
+    }
+
+    fixed_count
+}
@@ -0,0 +1,139 @@
+use super::cleaner::{move_waypoint, time_in_s};
+use clap::ValueEnum;
+use geo::{Distance, Haversine, Point};
+use gpx::{Gpx, TrackSegment, Waypoint};
+
+pub const DEFAULT_PAUSE_RADIUS: f64 = 20.0;
+pub const DEFAULT_PAUSE_DURATION: f64 = 120.0;
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PauseAction {
+    /// Replace the points of a pause with a single point at their centre.
+    Collapse,
+    /// Remove all points of a pause.
+    Remove,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauseConfig {
+    /// Maximum distance (in metres) of the points of a pause from their centre.
+    pub radius_in_m: f64,
+    /// Minimum duration (in seconds) for the points within the radius to count as a pause.
+    pub min_duration_in_s: f64,
+    pub action: PauseAction,
+    /// End the track segment at each pause and start a new one after it.
+    pub split_segments: bool,
+}
+
+/// Detects periods in which the device did not move (all points stay within a radius for at
+/// least the minimum duration) and collapses or removes them. Only track points with timestamps
+/// are considered. Returns the number of pauses.
+pub fn remove_pauses(gpx: &mut Gpx, config: &PauseConfig) -> usize {
+    let mut pause_count = 0;
+
+    for track in gpx.tracks.iter_mut() {
+        let mut segments = Vec::with_capacity(track.segments.len());
+
+        for segment in track.segments.drain(..) {
+            let pauses = find_pauses(&segment.points, config);
+            pause_count += pauses.len();
+
+            if pauses.is_empty() {
+                segments.push(segment);
+                continue;
+            }
+
+            let mut current = TrackSegment::new();
+            let mut next_index = 0;
+
+            for (start, end) in pauses {
+                current
+                    .points
+                    .extend_from_slice(&segment.points[next_index..start]);
+
+                if config.action == PauseAction::Collapse {
+                    current
+                        .points
+                        .push(collapse_points(&segment.points[start..=end]));
+                }
+
+                if config.split_segments && !current.points.is_empty() {
+                    segments.push(current);
+                    current = TrackSegment::new();
+                }
+
+                next_index = end + 1;
+            }
+
+            current
+                .points
+                .extend_from_slice(&segment.points[next_index..]);
+            if !current.points.is_empty() {
+                segments.push(current);
+            }
+        }
+
+        track.segments = segments;
+    }
+
+    pause_count
+}
+
+/// Returns the first and last index of each pause.
+fn find_pauses(points: &[Waypoint], config: &PauseConfig) -> Vec<(usize, usize)> {
+    let mut pauses = Vec::new();
+    let mut start = 0;
+
+    while start < points.len() {
+        // Without a start time, no duration can be measured, so the cluster cannot be a pause.
+        let Some(start_time) = time_in_s(&points[start]) else {
+            start += 1;
+            continue;
+        };
+
+        let mut centre = points[start].point();
+        let mut end = start;
+        let mut end_time = start_time;
+
+        // Grow the cluster while the next point stays within the radius of its centre.
+        while end + 1 < points.len()
+            && Haversine.distance(centre, points[end + 1].point()) <= config.radius_in_m
+        {
+            end += 1;
+            let count = (end - start + 1) as f64;
+            let point = points[end].point();
+            centre = Point::new(
+                centre.x() + (point.x() - centre.x()) / count,
+                centre.y() + (point.y() - centre.y()) / count,
+            );
+            if let Some(time) = time_in_s(&points[end]) {
+                end_time = time;
+            }
+        }
+
+        let duration_in_s = end_time - start_time;
+
+        if end > start && duration_in_s >= config.min_duration_in_s {
+            pauses.push((start, end));
+            start = end + 1;
+        } else if end + 1 == points.len() {
+            // Clusters starting later end at the same point, so they are even shorter.
+            break;
+        } else {
+            start += 1;
+        }
+    }
+
+    pauses
+}
+
+/// Returns a single point at the centre of the given points, with the remaining data (e.g.
+/// the time) taken from the first point.
+fn collapse_points(points: &[Waypoint]) -> Waypoint {
+    let count = points.len() as f64;
+    let (sum_x, sum_y) = points.iter().fold((0.0, 0.0), |(x, y), point| {
+        (x + point.point().x(), y + point.point().y())
+    });
+
+    move_waypoint(&points[0], Point::new(sum_x / count, sum_y / count))
+}
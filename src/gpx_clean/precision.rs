@@ -0,0 +1,85 @@
+use super::cleaner::{move_waypoint, polyline_points_mut};
+use geo::Point;
+use gpx::{Gpx, Waypoint};
+
+/// Number of decimals to keep for coordinates and elevations. `None` keeps the full precision.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrecisionConfig {
+    pub coordinate_decimals: Option<u32>,
+    pub elevation_decimals: Option<u32>,
+}
+
+impl PrecisionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.coordinate_decimals.is_some() || self.elevation_decimals.is_some()
+    }
+}
+
+/// Formats the value with at most the given number of decimals, without trailing zeros.
+pub fn format_with_decimals(value: f64, decimals: u32) -> String {
+    let text = format!("{:.*}", decimals as usize, value);
+
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+
+    // Avoid "-0" for small negative values.
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Rounds the value to the given number of decimals. Goes through the decimal representation,
+/// so that the result is printed without floating point artifacts (e.g. 0.30000000000000004).
+pub fn round_to_decimals(value: f64, decimals: u32) -> f64 {
+    format_with_decimals(value, decimals)
+        .parse()
+        .unwrap_or(value)
+}
+
+/// Rounds the coordinates and elevations of all points, and removes consecutive track and route
+/// points that become identical. Returns the number of removed points.
+pub fn reduce_precision(gpx: &mut Gpx, config: &PrecisionConfig) -> usize {
+    for waypoint in gpx.waypoints.iter_mut() {
+        round_waypoint(waypoint, config);
+    }
+
+    let mut removed_count = 0;
+
+    for points in polyline_points_mut(gpx) {
+        for point in points.iter_mut() {
+            round_waypoint(point, config);
+        }
+
+        let original_count = points.len();
+        points.dedup_by(|point, previous| {
+            point.point() == previous.point() && point.elevation == previous.elevation
+        });
+        removed_count += original_count - points.len();
+    }
+
+    removed_count
+}
+
+fn round_waypoint(waypoint: &mut Waypoint, config: &PrecisionConfig) {
+    if let Some(decimals) = config.coordinate_decimals {
+        let point = waypoint.point();
+        *waypoint = move_waypoint(
+            waypoint,
+            Point::new(
+                round_to_decimals(point.x(), decimals),
+                round_to_decimals(point.y(), decimals),
+            ),
+        );
+    }
+
+    if let Some(decimals) = config.elevation_decimals {
+        waypoint.elevation = waypoint
+            .elevation
+            .map(|elevation| round_to_decimals(elevation, decimals));
+    }
+}
@@ -0,0 +1,198 @@
+use super::metadata;
+use geo::{Distance, Haversine, Point};
+use gpx::{Gpx, TrackSegment, Waypoint};
+
+pub const DEFAULT_PRIVACY_RADIUS: f64 = 200.0;
+
+/// Circular area in which all points are removed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PrivacyZone {
+    pub centre: Point,
+    pub radius_in_m: f64,
+}
+
+impl PrivacyZone {
+    pub fn contains(&self, point: Point) -> bool {
+        Haversine.distance(self.centre, point) <= self.radius_in_m
+    }
+}
+
+/// Parses a privacy zone in the format "lat,lon,radius", where the radius is in metres.
+/// The radius may be omitted, in which case the default radius is used.
+pub fn parse_privacy_zone(text: &str) -> Result<PrivacyZone, String> {
+    let parts: Vec<&str> = text.split(',').map(str::trim).collect();
+
+    let parse_number = |part: &str| {
+        part.parse::<f64>()
+            .map_err(|_| format!("invalid number '{part}' in privacy zone '{text}'"))
+    };
+
+    let (latitude, longitude, radius_in_m) = match parts.as_slice() {
+        [latitude, longitude] => (
+            parse_number(latitude)?,
+            parse_number(longitude)?,
+            DEFAULT_PRIVACY_RADIUS,
+        ),
+        [latitude, longitude, radius] => (
+            parse_number(latitude)?,
+            parse_number(longitude)?,
+            parse_number(radius)?,
+        ),
+        _ => {
+            return Err(format!(
+                "invalid privacy zone '{text}' (expected lat,lon,radius)"
+            ));
+        }
+    };
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(format!(
+            "coordinates of privacy zone '{text}' are out of range"
+        ));
+    }
+
+    if radius_in_m < 0.0 {
+        return Err("privacy zone radius must not be negative".to_string());
+    }
+
+    Ok(PrivacyZone {
+        centre: Point::new(longitude, latitude),
+        radius_in_m,
+    })
+}
+
+/// Creates a privacy zone around each waypoint.
+pub fn privacy_zones_from_waypoints(waypoints: &[Waypoint], radius_in_m: f64) -> Vec<PrivacyZone> {
+    waypoints
+        .iter()
+        .map(|waypoint| PrivacyZone {
+            centre: waypoint.point(),
+            radius_in_m,
+        })
+        .collect()
+}
+
+/// Removes all track points, route points, and waypoints inside the zones. Track segments are
+/// split where points were removed, so that no line crosses a zone. Returns the number of
+/// removed points.
+pub fn remove_points_in_zones(gpx: &mut Gpx, zones: &[PrivacyZone]) -> usize {
+    let is_hidden = |point: &Waypoint| zones.iter().any(|zone| zone.contains(point.point()));
+    let mut removed_count = 0;
+
+    for track in gpx.tracks.iter_mut() {
+        let mut segments = Vec::with_capacity(track.segments.len());
+
+        for segment in track.segments.drain(..) {
+            let mut current = TrackSegment::new();
+
+            for point in segment.points {
+                if is_hidden(&point) {
+                    removed_count += 1;
+                    if !current.points.is_empty() {
+                        segments.push(current);
+                        current = TrackSegment::new();
+                    }
+                } else {
+                    current.points.push(point);
+                }
+            }
+
+            if !current.points.is_empty() {
+                segments.push(current);
+            }
+        }
+
+        track.segments = segments;
+    }
+
+    for route in gpx.routes.iter_mut() {
+        let original_count = route.points.len();
+        route.points.retain(|point| !is_hidden(point));
+        removed_count += original_count - route.points.len();
+    }
+
+    let original_count = gpx.waypoints.len();
+    gpx.waypoints.retain(|point| !is_hidden(point));
+    removed_count += original_count - gpx.waypoints.len();
+
+    if removed_count > 0 {
+        update_bounds(gpx);
+    }
+
+    removed_count
+}
+
+/// Removes the points within the first `start_in_m` and the last `end_in_m` metres of each
+/// track. The distance is measured along the track. Returns the number of removed points.
+pub fn trim_tracks(gpx: &mut Gpx, start_in_m: f64, end_in_m: f64) -> usize {
+    let mut removed_count = 0;
+
+    for track in gpx.tracks.iter_mut() {
+        if start_in_m > 0.0 {
+            removed_count += trim_segments_from_start(&mut track.segments, start_in_m);
+        }
+
+        if end_in_m > 0.0 {
+            reverse_segments(&mut track.segments);
+            removed_count += trim_segments_from_start(&mut track.segments, end_in_m);
+            reverse_segments(&mut track.segments);
+        }
+    }
+
+    if removed_count > 0 {
+        update_bounds(gpx);
+    }
+
+    removed_count
+}
+
+/// Recomputes the bounds in the metadata, as the bounds from the input could reveal the
+/// locations of the removed points.
+fn update_bounds(gpx: &mut Gpx) {
+    if let Some(metadata) = &mut gpx.metadata
+        && metadata.bounds.take().is_some()
+    {
+        metadata::set_bounds(gpx);
+    }
+}
+
+fn trim_segments_from_start(segments: &mut Vec<TrackSegment>, distance_in_m: f64) -> usize {
+    let mut removed_count = 0;
+    let mut travelled_in_m = 0.0;
+
+    // The gaps between segments do not count towards the distance.
+    for segment in segments.iter_mut() {
+        let mut previous: Option<Point> = None;
+        let mut keep_from = segment.points.len();
+
+        for (i, point) in segment.points.iter().enumerate() {
+            if let Some(previous) = previous {
+                travelled_in_m += Haversine.distance(previous, point.point());
+            }
+            previous = Some(point.point());
+
+            if travelled_in_m >= distance_in_m {
+                keep_from = i;
+                break;
+            }
+        }
+
+        removed_count += keep_from;
+        segment.points.drain(..keep_from);
+
+        if travelled_in_m >= distance_in_m {
+            break;
+        }
+    }
+
+    segments.retain(|segment| !segment.points.is_empty());
+
+    removed_count
+}
+
+fn reverse_segments(segments: &mut [TrackSegment]) {
+    segments.reverse();
+    for segment in segments.iter_mut() {
+        segment.points.reverse();
+    }
+}
@@ -0,0 +1,282 @@
+use crate::error_messages;
+use crate::gpx_info::info::compute_distance_of_polyline_in_metres;
+use anyhow::Context;
+use clap::ValueEnum;
+use gpx::{Gpx, Waypoint};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use time::{Date, OffsetDateTime};
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RenameTarget {
+    Tracks,
+    Routes,
+    Waypoints,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    /// Name of the input file, without extension.
+    File,
+    /// 1-based index of the item among the items of the same kind.
+    Index,
+    /// Date of the first timestamp.
+    Date,
+    DistanceKm,
+    /// The current name.
+    Original,
+}
+
+/// A name template such as "{file} {index}".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+/// Source of the new names.
+#[derive(Clone, Debug)]
+pub enum NameSource {
+    Template {
+        template: NameTemplate,
+        file_name: String,
+    },
+    /// Maps the original names to new names.
+    Mapping(HashMap<String, String>),
+    /// New names in the order of the items. Empty names keep the original name.
+    List(Vec<String>),
+}
+
+/// Values that can be inserted into a template.
+struct NameContext<'a> {
+    index: usize,
+    original: Option<&'a str>,
+    date: Option<Date>,
+    distance_in_m: Option<f64>,
+}
+
+/// Parses a name template. Supported placeholders: {file}, {index}, {date}, {distance_km},
+/// and {original}.
+pub fn parse_name_template(text: &str) -> Result<NameTemplate, String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            parts.push(TemplatePart::Text(rest[..start].to_string()));
+        }
+
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("unclosed placeholder in name template '{text}'"))?
+            + start;
+
+        let part = match &rest[start + 1..end] {
+            "file" => TemplatePart::File,
+            "index" => TemplatePart::Index,
+            "date" => TemplatePart::Date,
+            "distance_km" => TemplatePart::DistanceKm,
+            "original" => TemplatePart::Original,
+            placeholder => {
+                return Err(format!(
+                    "unknown placeholder '{{{placeholder}}}' (expected {{file}}, {{index}}, \
+                    {{date}}, {{distance_km}} or {{original}})"
+                ));
+            }
+        };
+        parts.push(part);
+        rest = &rest[end + 1..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest.to_string()));
+    }
+
+    Ok(NameTemplate { parts })
+}
+
+impl NameTemplate {
+    /// Renders the name, or returns `None` if it would be empty.
+    fn render(&self, file_name: &str, context: &NameContext) -> Option<String> {
+        let mut name = String::new();
+        // Set after an empty placeholder, so that the text around it does not leave a double
+        // space behind.
+        let mut after_empty_placeholder = false;
+
+        for part in &self.parts {
+            let value = match part {
+                TemplatePart::Text(text) => {
+                    let text = if after_empty_placeholder
+                        && (name.is_empty() || name.ends_with(char::is_whitespace))
+                    {
+                        text.trim_start()
+                    } else {
+                        text.as_str()
+                    };
+                    if !text.is_empty() {
+                        after_empty_placeholder = false;
+                    }
+                    name.push_str(text);
+                    continue;
+                }
+                TemplatePart::File => file_name.to_string(),
+                TemplatePart::Index => context.index.to_string(),
+                TemplatePart::Date => context
+                    .date
+                    .map(|date| {
+                        format!(
+                            "{:04}-{:02}-{:02}",
+                            date.year(),
+                            date.month() as u8,
+                            date.day()
+                        )
+                    })
+                    .unwrap_or_default(),
+                TemplatePart::DistanceKm => context
+                    .distance_in_m
+                    .map(|distance_in_m| format!("{:.1}", distance_in_m / 1000.0))
+                    .unwrap_or_default(),
+                TemplatePart::Original => context.original.unwrap_or_default().to_string(),
+            };
+
+            if value.is_empty() {
+                after_empty_placeholder = true;
+            } else {
+                after_empty_placeholder = false;
+                name.push_str(&value);
+            }
+        }
+
+        if after_empty_placeholder {
+            name.truncate(name.trim_end().len());
+        }
+
+        (!name.trim().is_empty()).then_some(name)
+    }
+}
+
+/// Reads a names file. A ".csv" file maps original names (first column) to new names
+/// (second column). Any other file contains one new name per line.
+pub fn read_names_file(path: &Path) -> anyhow::Result<NameSource> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+    if is_csv {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(path)
+            .with_context(|| error_messages::NAMES_FILE_READ_ERROR)?;
+
+        let mut mapping = HashMap::new();
+        for record in reader.records() {
+            let record = record.with_context(|| error_messages::NAMES_FILE_READ_ERROR)?;
+            if let (Some(original), Some(name)) = (record.get(0), record.get(1)) {
+                mapping.insert(original.trim().to_string(), name.trim().to_string());
+            }
+        }
+
+        Ok(NameSource::Mapping(mapping))
+    } else {
+        let contents =
+            fs::read_to_string(path).with_context(|| error_messages::NAMES_FILE_READ_ERROR)?;
+        let names = contents
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        Ok(NameSource::List(names))
+    }
+}
+
+/// Renames the selected kinds of items (in the order tracks, routes, waypoints).
+/// Returns the number of renamed items.
+pub fn rename_items(gpx: &mut Gpx, source: &NameSource, targets: &[RenameTarget]) -> usize {
+    let mut renamed_count = 0;
+    let mut list_position = 0;
+
+    let mut rename = |name: &mut Option<String>, context: NameContext| {
+        let new_name = match source {
+            NameSource::Template {
+                template,
+                file_name,
+            } => template.render(file_name, &context),
+            NameSource::Mapping(mapping) => context
+                .original
+                .and_then(|original| mapping.get(original))
+                .cloned(),
+            NameSource::List(names) => {
+                let new_name = names.get(list_position).filter(|name| !name.is_empty());
+                list_position += 1;
+                new_name.cloned()
+            }
+        };
+
+        if let Some(new_name) = new_name
+            && name.as_ref() != Some(&new_name)
+        {
+            *name = Some(new_name);
+            renamed_count += 1;
+        }
+    };
+
+    if targets.contains(&RenameTarget::Tracks) {
+        for (i, track) in gpx.tracks.iter_mut().enumerate() {
+            let points = track.segments.iter().flat_map(|segment| &segment.points);
+            let context = NameContext {
+                index: i + 1,
+                original: track.name.as_deref(),
+                date: get_first_date(points),
+                distance_in_m: Some(
+                    track
+                        .segments
+                        .iter()
+                        .map(|segment| compute_distance_of_polyline_in_metres(&segment.points))
+                        .sum(),
+                ),
+            };
+            let mut name = track.name.clone();
+            rename(&mut name, context);
+            track.name = name;
+        }
+    }
+
+    if targets.contains(&RenameTarget::Routes) {
+        for (i, route) in gpx.routes.iter_mut().enumerate() {
+            let context = NameContext {
+                index: i + 1,
+                original: route.name.as_deref(),
+                date: get_first_date(&route.points),
+                distance_in_m: Some(compute_distance_of_polyline_in_metres(&route.points)),
+            };
+            let mut name = route.name.clone();
+            rename(&mut name, context);
+            route.name = name;
+        }
+    }
+
+    if targets.contains(&RenameTarget::Waypoints) {
+        for (i, waypoint) in gpx.waypoints.iter_mut().enumerate() {
+            let context = NameContext {
+                index: i + 1,
+                original: waypoint.name.as_deref(),
+                date: get_first_date([&*waypoint]),
+                distance_in_m: None,
+            };
+            let mut name = waypoint.name.clone();
+            rename(&mut name, context);
+            waypoint.name = name;
+        }
+    }
+
+    renamed_count
+}
+
+fn get_first_date<'a>(points: impl IntoIterator<Item = &'a Waypoint>) -> Option<Date> {
+    points
+        .into_iter()
+        .find_map(|point| point.time)
+        .map(|time| OffsetDateTime::from(time).date())
+}
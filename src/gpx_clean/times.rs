@@ -0,0 +1,191 @@
+use crate::error_messages;
+use anyhow::Context;
+use gpx::{Gpx, Time, Waypoint};
+use time::{Duration, OffsetDateTime};
+
+/// Default instant at which anonymised recordings start.
+pub const DEFAULT_ANONYMISED_START: &str = "2000-01-01T00:00:00Z";
+
+/// Parses a time offset such as "+2h", "-1h30m", "90s", or "-05:00" (hours and minutes, with
+/// optional seconds).
+pub fn parse_time_offset(text: &str) -> Result<Duration, String> {
+    let invalid =
+        || format!("invalid time offset '{text}' (expected e.g. +2h, -1h30m, 90s, or -05:00)");
+
+    let trimmed = text.trim();
+    let (sign, rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let seconds = if rest.contains(':') {
+        let parts = rest
+            .split(':')
+            .map(|part| part.parse::<i64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (hours, minutes, seconds) = match parts.as_slice() {
+            [hours, minutes] => (*hours, *minutes, 0),
+            [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+            _ => return Err(invalid()),
+        };
+
+        hours
+            .checked_mul(3600)
+            .and_then(|total| total.checked_add(minutes.checked_mul(60)?))
+            .and_then(|total| total.checked_add(seconds))
+            .ok_or_else(invalid)?
+    } else {
+        let mut seconds: i64 = 0;
+        let mut number = String::new();
+
+        for c in rest.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+
+            let factor = match c {
+                'd' => 86400,
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return Err(invalid()),
+            };
+            seconds = number
+                .parse::<i64>()
+                .ok()
+                .and_then(|value| value.checked_mul(factor))
+                .and_then(|value| seconds.checked_add(value))
+                .ok_or_else(invalid)?;
+            number.clear();
+        }
+
+        // A trailing number without unit is in seconds.
+        if !number.is_empty() {
+            seconds = number
+                .parse::<i64>()
+                .ok()
+                .and_then(|value| seconds.checked_add(value))
+                .ok_or_else(invalid)?;
+        }
+
+        seconds
+    };
+
+    Ok(Duration::seconds(sign * seconds))
+}
+
+/// Returns the earliest timestamp of all track points, route points, and waypoints.
+pub fn earliest_point_time(gpx: &Gpx) -> Option<OffsetDateTime> {
+    all_points(gpx)
+        .filter_map(|point| point.time)
+        .map(OffsetDateTime::from)
+        .min()
+}
+
+/// Shifts the timestamps of all points and the time in the metadata by the offset. Returns the
+/// number of shifted timestamps, or an error if a shifted timestamp is out of range.
+pub fn shift_times(gpx: &mut Gpx, offset: Duration) -> anyhow::Result<usize> {
+    let mut shifted_count = 0;
+
+    let mut shift = |time: &mut Option<Time>| -> anyhow::Result<()> {
+        if let Some(value) = time {
+            let shifted = OffsetDateTime::from(*value)
+                .checked_add(offset)
+                .context(error_messages::TIME_OUT_OF_RANGE)?;
+            *value = Time::from(shifted);
+            shifted_count += 1;
+        }
+        Ok(())
+    };
+
+    if let Some(metadata) = &mut gpx.metadata {
+        shift(&mut metadata.time)?;
+    }
+
+    for point in all_points_mut(gpx) {
+        shift(&mut point.time)?;
+    }
+
+    Ok(shifted_count)
+}
+
+/// Shifts all timestamps, so that the earliest point time is at `start`. The intervals between
+/// the points are preserved. Returns the number of shifted timestamps.
+pub fn shift_times_to_start(gpx: &mut Gpx, start: OffsetDateTime) -> anyhow::Result<usize> {
+    match earliest_point_time(gpx) {
+        Some(earliest) => shift_times(gpx, start - earliest),
+        None => Ok(0),
+    }
+}
+
+/// Hides the real date and time of day by shifting all timestamps, so that the earliest point
+/// time is at `start`. The order of and intervals between the points are preserved, so that
+/// speeds and durations can still be analysed. Returns the number of anonymised timestamps.
+pub fn anonymise_times(gpx: &mut Gpx, start: OffsetDateTime) -> anyhow::Result<usize> {
+    if earliest_point_time(gpx).is_some() {
+        return shift_times_to_start(gpx, start);
+    }
+
+    // Without point times, there is nothing to align the metadata time with.
+    match &mut gpx.metadata {
+        Some(metadata) if metadata.time.is_some() => {
+            metadata.time = Some(Time::from(start));
+            Ok(1)
+        }
+        _ => Ok(0),
+    }
+}
+
+/// Removes the timestamps of all points and the time in the metadata. Returns the number of
+/// removed timestamps.
+pub fn remove_times(gpx: &mut Gpx) -> usize {
+    let mut removed_count = 0;
+
+    if let Some(metadata) = &mut gpx.metadata
+        && metadata.time.take().is_some()
+    {
+        removed_count += 1;
+    }
+
+    for point in all_points_mut(gpx) {
+        if point.time.take().is_some() {
+            removed_count += 1;
+        }
+    }
+
+    removed_count
+}
+
+fn all_points(gpx: &Gpx) -> impl Iterator<Item = &Waypoint> {
+    let track_points = gpx
+        .tracks
+        .iter()
+        .flat_map(|track| &track.segments)
+        .flat_map(|segment| &segment.points);
+    let route_points = gpx.routes.iter().flat_map(|route| &route.points);
+
+    gpx.waypoints.iter().chain(track_points).chain(route_points)
+}
+
+fn all_points_mut(gpx: &mut Gpx) -> impl Iterator<Item = &mut Waypoint> {
+    let track_points = gpx
+        .tracks
+        .iter_mut()
+        .flat_map(|track| track.segments.iter_mut())
+        .flat_map(|segment| segment.points.iter_mut());
+    let route_points = gpx
+        .routes
+        .iter_mut()
+        .flat_map(|route| route.points.iter_mut());
+
+    gpx.waypoints
+        .iter_mut()
+        .chain(track_points)
+        .chain(route_points)
+}
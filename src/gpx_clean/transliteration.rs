@@ -0,0 +1,90 @@
+use deunicode::deunicode;
+use gpx::{Gpx, Link, Waypoint};
+
+/// Replaces the non-ASCII characters in all text fields with their closest ASCII equivalents,
+/// e.g. "Großglockner Südwand" becomes "Grossglockner Sudwand". Greek and Cyrillic text is
+/// romanised.
+pub fn transliterate_text_fields(gpx: &mut Gpx) {
+    transliterate_optional(&mut gpx.creator);
+
+    if let Some(metadata) = &mut gpx.metadata {
+        transliterate_optional(&mut metadata.name);
+        transliterate_optional(&mut metadata.description);
+        transliterate_optional(&mut metadata.keywords);
+        transliterate_links(&mut metadata.links);
+
+        if let Some(author) = &mut metadata.author {
+            transliterate_optional(&mut author.name);
+            transliterate_optional(&mut author.email);
+            if let Some(link) = &mut author.link {
+                transliterate_optional(&mut link.text);
+            }
+        }
+
+        if let Some(copyright) = &mut metadata.copyright {
+            transliterate_optional(&mut copyright.author);
+            transliterate_optional(&mut copyright.license);
+        }
+    }
+
+    for waypoint in gpx.waypoints.iter_mut() {
+        transliterate_waypoint(waypoint);
+    }
+
+    for track in gpx.tracks.iter_mut() {
+        transliterate_optional(&mut track.name);
+        transliterate_optional(&mut track.comment);
+        transliterate_optional(&mut track.description);
+        transliterate_optional(&mut track.source);
+        transliterate_optional(&mut track.type_);
+        transliterate_links(&mut track.links);
+
+        for segment in track.segments.iter_mut() {
+            for point in segment.points.iter_mut() {
+                transliterate_waypoint(point);
+            }
+        }
+    }
+
+    for route in gpx.routes.iter_mut() {
+        transliterate_optional(&mut route.name);
+        transliterate_optional(&mut route.comment);
+        transliterate_optional(&mut route.description);
+        transliterate_optional(&mut route.source);
+        transliterate_optional(&mut route.type_);
+        transliterate_links(&mut route.links);
+
+        for point in route.points.iter_mut() {
+            transliterate_waypoint(point);
+        }
+    }
+}
+
+fn transliterate_waypoint(waypoint: &mut Waypoint) {
+    transliterate_optional(&mut waypoint.name);
+    transliterate_optional(&mut waypoint.comment);
+    transliterate_optional(&mut waypoint.description);
+    transliterate_optional(&mut waypoint.source);
+    transliterate_optional(&mut waypoint.symbol);
+    transliterate_optional(&mut waypoint.type_);
+    transliterate_links(&mut waypoint.links);
+}
+
+/// Only transliterates the link texts. The URLs are kept as they are.
+fn transliterate_links(links: &mut [Link]) {
+    for link in links.iter_mut() {
+        transliterate_optional(&mut link.text);
+    }
+}
+
+fn transliterate_optional(text: &mut Option<String>) {
+    if let Some(text) = text {
+        transliterate(text);
+    }
+}
+
+pub fn transliterate(text: &mut String) {
+    if !text.is_ascii() {
+        *text = deunicode(text);
+    }
+}
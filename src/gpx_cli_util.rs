@@ -1,5 +1,5 @@
 use crate::{error_messages, util};
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use gpx::Gpx;
 use log::info;
 use std::fs;
@@ -50,3 +50,74 @@ pub fn write_gpx_file(gpx: &Gpx, output_path: &Path) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Expands the given input paths into a list of files. Each input may be a file, a directory
+/// (from which all GPX files are collected), or a glob pattern such as `tracks/*.gpx`.
+pub fn collect_input_files(inputs: &[PathBuf], recursive: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        if input.is_dir() {
+            collect_gpx_files_in_directory(input, recursive, &mut files)?;
+        } else if input.exists() {
+            files.push(input.clone());
+        } else {
+            let pattern = input.to_string_lossy();
+            let matches =
+                glob::glob(&pattern).with_context(|| error_messages::INVALID_GLOB_PATTERN)?;
+
+            let mut found_match = false;
+            for path in matches.flatten() {
+                found_match = true;
+                if path.is_dir() {
+                    collect_gpx_files_in_directory(&path, recursive, &mut files)?;
+                } else {
+                    files.push(path);
+                }
+            }
+
+            if !found_match {
+                return Err(anyhow!(
+                    "{} ('{}')",
+                    error_messages::INPUT_PATH_NOT_FOUND,
+                    input.display()
+                ));
+            }
+        }
+    }
+
+    if files.is_empty() {
+        return Err(anyhow!(error_messages::NO_INPUT_FILES_FOUND));
+    }
+
+    Ok(files)
+}
+
+fn collect_gpx_files_in_directory(
+    directory: &Path,
+    recursive: bool,
+    files: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)
+        .with_context(|| error_messages::DIRECTORY_READ_ERROR)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                collect_gpx_files_in_directory(&path, recursive, files)?;
+            }
+        } else if is_gpx_file(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_gpx_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gpx"))
+}
@@ -0,0 +1,47 @@
+use super::differ;
+use super::differ::{DEFAULT_MATCH_DISTANCE, DiffConfig};
+use super::output;
+use super::output::OutputFormat;
+use crate::gpx_cli_util;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Original GPX file path.
+    old: PathBuf,
+
+    /// Changed GPX file path.
+    new: PathBuf,
+
+    /// List unchanged tracks, routes, and waypoints as well.
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+
+    /// Output format.
+    #[arg(short = 'f', long = "format", default_value = "text")]
+    format: OutputFormat,
+
+    /// Maximum mean distance (in metres) between tracks / routes / waypoints with different
+    /// names to consider them the same, renamed item.
+    #[arg(long = "match-distance", default_value_t = DEFAULT_MATCH_DISTANCE)]
+    match_distance: f64,
+}
+
+pub fn run_cli() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_cli_with_args(args)
+}
+
+pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
+    let old = gpx_cli_util::read_input_gpx_file(&args.old)?;
+    let new = gpx_cli_util::read_input_gpx_file(&args.new)?;
+
+    let config = DiffConfig {
+        match_distance_in_m: args.match_distance,
+    };
+
+    let diff = differ::compare_gpx(&old, &new, &config);
+
+    output::print_diff(&diff, args.format, args.verbose)
+}
@@ -0,0 +1,538 @@
+use crate::geo_util::{LocalProjection, closest_point_on_segment, distance};
+use crate::gpx_info::info::compute_distance_of_polyline_in_metres;
+use geo::{Coord, Distance, Haversine};
+use gpx::{Gpx, Link, Metadata, Waypoint};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+pub const DEFAULT_MATCH_DISTANCE: f64 = 50.0;
+
+/// Maximum number of points per line used to find matching tracks / routes with different names.
+const MAX_MATCHING_SAMPLES: usize = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffConfig {
+    /// Maximum mean deviation (in metres) for two tracks / routes with different names to be
+    /// considered the same, renamed line. Also used for waypoints.
+    pub match_distance_in_m: f64,
+}
+
+#[derive(Serialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Renamed,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Geometric difference between the old and the new version of a track or route.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GeometryDiff {
+    /// Largest distance of a point of either line from the other line.
+    pub max_deviation_in_m: f64,
+    /// Average distance of the points of both lines from the other line.
+    pub mean_deviation_in_m: f64,
+    pub old_point_count: usize,
+    pub new_point_count: usize,
+    pub old_distance_in_m: f64,
+    pub new_distance_in_m: f64,
+    pub distance_delta_in_m: f64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LineDiff {
+    pub change: ChangeKind,
+    /// 0-based index in the old file.
+    pub old_index: Option<usize>,
+    /// 0-based index in the new file.
+    pub new_index: Option<usize>,
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    pub geometry: Option<GeometryDiff>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct WaypointDiff {
+    pub change: ChangeKind,
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    /// Distance between the old and the new position.
+    pub moved_by_in_m: Option<f64>,
+    /// Changed attributes, apart from the name and position.
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GpxDiff {
+    pub metadata: Vec<FieldChange>,
+    pub tracks: Vec<LineDiff>,
+    pub routes: Vec<LineDiff>,
+    pub waypoints: Vec<WaypointDiff>,
+}
+
+impl GpxDiff {
+    pub fn has_differences(&self) -> bool {
+        !self.metadata.is_empty()
+            || self
+                .tracks
+                .iter()
+                .chain(&self.routes)
+                .any(|line| line.change != ChangeKind::Unchanged)
+            || self
+                .waypoints
+                .iter()
+                .any(|waypoint| waypoint.change != ChangeKind::Unchanged)
+    }
+}
+
+/// A track or route, reduced to what is needed for the comparison.
+struct Line<'a> {
+    name: Option<&'a str>,
+    polylines: Vec<&'a [Waypoint]>,
+}
+
+impl Line<'_> {
+    fn points(&self) -> impl Iterator<Item = &Waypoint> {
+        self.polylines.iter().flat_map(|points| points.iter())
+    }
+}
+
+pub fn compare_gpx(old: &Gpx, new: &Gpx, config: &DiffConfig) -> GpxDiff {
+    let all_points = [old, new].into_iter().flat_map(|gpx| {
+        gpx.tracks
+            .iter()
+            .flat_map(|track| track.segments.iter().flat_map(|segment| &segment.points))
+            .chain(gpx.routes.iter().flat_map(|route| &route.points))
+            .chain(&gpx.waypoints)
+            .map(|point| point.point())
+    });
+    let projection = LocalProjection::centred_on(all_points)
+        .unwrap_or_else(|| LocalProjection::new(geo::Point::new(0.0, 0.0)));
+
+    GpxDiff {
+        metadata: compare_metadata(old.metadata.as_ref(), new.metadata.as_ref()),
+        tracks: compare_lines(
+            &get_track_lines(old),
+            &get_track_lines(new),
+            &projection,
+            config,
+        ),
+        routes: compare_lines(
+            &get_route_lines(old),
+            &get_route_lines(new),
+            &projection,
+            config,
+        ),
+        waypoints: compare_waypoints(&old.waypoints, &new.waypoints, config),
+    }
+}
+
+fn get_track_lines(gpx: &Gpx) -> Vec<Line<'_>> {
+    gpx.tracks
+        .iter()
+        .map(|track| Line {
+            name: track.name.as_deref(),
+            polylines: track
+                .segments
+                .iter()
+                .map(|segment| segment.points.as_slice())
+                .collect(),
+        })
+        .collect()
+}
+
+fn get_route_lines(gpx: &Gpx) -> Vec<Line<'_>> {
+    gpx.routes
+        .iter()
+        .map(|route| Line {
+            name: route.name.as_deref(),
+            polylines: vec![route.points.as_slice()],
+        })
+        .collect()
+}
+
+fn compare_metadata(old: Option<&Metadata>, new: Option<&Metadata>) -> Vec<FieldChange> {
+    let fields = |metadata: Option<&Metadata>| -> Vec<(&'static str, Option<String>)> {
+        let author = metadata.and_then(|metadata| metadata.author.as_ref());
+        let copyright = metadata.and_then(|metadata| metadata.copyright.as_ref());
+
+        vec![
+            ("name", metadata.and_then(|m| m.name.clone())),
+            ("description", metadata.and_then(|m| m.description.clone())),
+            ("author name", author.and_then(|a| a.name.clone())),
+            ("author email", author.and_then(|a| a.email.clone())),
+            ("time", metadata.and_then(|m| m.time).map(format_time)),
+            ("keywords", metadata.and_then(|m| m.keywords.clone())),
+            ("copyright author", copyright.and_then(|c| c.author.clone())),
+            (
+                "copyright year",
+                copyright.and_then(|c| c.year).map(|year| year.to_string()),
+            ),
+            (
+                "copyright license",
+                copyright.and_then(|c| c.license.clone()),
+            ),
+            (
+                "links",
+                metadata
+                    .filter(|m| !m.links.is_empty())
+                    .map(|m| format_links(&m.links)),
+            ),
+        ]
+    };
+
+    fields(old)
+        .into_iter()
+        .zip(fields(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange { field, old, new })
+        .collect()
+}
+
+/// Matches the lines by name first. The remaining lines are matched by geometry and
+/// reported as renamed if they are close enough.
+fn compare_lines(
+    old: &[Line],
+    new: &[Line],
+    projection: &LocalProjection,
+    config: &DiffConfig,
+) -> Vec<LineDiff> {
+    let project = |line: &Line| -> Vec<Vec<Coord>> {
+        line.polylines
+            .iter()
+            .map(|points| {
+                points
+                    .iter()
+                    .map(|point| projection.project(point.point()))
+                    .collect()
+            })
+            .collect()
+    };
+    let old_coords: Vec<_> = old.iter().map(project).collect();
+    let new_coords: Vec<_> = new.iter().map(project).collect();
+
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let mut new_matched = vec![false; new.len()];
+
+    for (i, old_line) in old.iter().enumerate() {
+        let Some(name) = old_line.name else {
+            continue;
+        };
+        if let Some(j) = (0..new.len()).find(|&j| !new_matched[j] && new[j].name == Some(name)) {
+            new_matched[j] = true;
+            pairs.push((i, j));
+        }
+    }
+
+    let mut old_matched = vec![false; old.len()];
+    for &(i, _) in &pairs {
+        old_matched[i] = true;
+    }
+
+    // Match the remaining lines by geometry, closest pairs first.
+    let old_samples: Vec<_> = old_coords.iter().map(|coords| sample(coords)).collect();
+    let new_samples: Vec<_> = new_coords.iter().map(|coords| sample(coords)).collect();
+
+    let mut candidates = Vec::new();
+    for i in (0..old.len()).filter(|&i| !old_matched[i]) {
+        for j in (0..new.len()).filter(|&j| !new_matched[j]) {
+            // Lines without points have no geometry to compare, so they never match.
+            if old_samples[i].is_empty() || new_samples[j].is_empty() {
+                continue;
+            }
+
+            let (_, mean) = compute_deviation(&old_samples[i], &new_coords[j])
+                .combine(compute_deviation(&new_samples[j], &old_coords[i]))
+                .result();
+            if mean <= config.match_distance_in_m {
+                candidates.push((mean, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    for (_, i, j) in candidates {
+        if !old_matched[i] && !new_matched[j] {
+            old_matched[i] = true;
+            new_matched[j] = true;
+            pairs.push((i, j));
+        }
+    }
+
+    pairs.sort();
+
+    let mut diffs: Vec<LineDiff> = pairs
+        .into_iter()
+        .map(|(i, j)| {
+            let geometry = compare_geometry(&old[i], &new[j], &old_coords[i], &new_coords[j]);
+            let is_modified = geometry.max_deviation_in_m > 0.0
+                || geometry.old_point_count != geometry.new_point_count;
+
+            let change = if old[i].name != new[j].name {
+                ChangeKind::Renamed
+            } else if is_modified {
+                ChangeKind::Modified
+            } else {
+                ChangeKind::Unchanged
+            };
+
+            LineDiff {
+                change,
+                old_index: Some(i),
+                new_index: Some(j),
+                old_name: old[i].name.map(str::to_string),
+                new_name: new[j].name.map(str::to_string),
+                geometry: Some(geometry),
+            }
+        })
+        .collect();
+
+    for (i, line) in old.iter().enumerate().filter(|(i, _)| !old_matched[*i]) {
+        diffs.push(LineDiff {
+            change: ChangeKind::Removed,
+            old_index: Some(i),
+            new_index: None,
+            old_name: line.name.map(str::to_string),
+            new_name: None,
+            geometry: None,
+        });
+    }
+
+    for (j, line) in new.iter().enumerate().filter(|(j, _)| !new_matched[*j]) {
+        diffs.push(LineDiff {
+            change: ChangeKind::Added,
+            old_index: None,
+            new_index: Some(j),
+            old_name: None,
+            new_name: line.name.map(str::to_string),
+            geometry: None,
+        });
+    }
+
+    diffs
+}
+
+fn compare_geometry(
+    old: &Line,
+    new: &Line,
+    old_coords: &[Vec<Coord>],
+    new_coords: &[Vec<Coord>],
+) -> GeometryDiff {
+    let old_points: Vec<Coord> = old_coords.iter().flatten().copied().collect();
+    let new_points: Vec<Coord> = new_coords.iter().flatten().copied().collect();
+
+    let (max_deviation_in_m, mean_deviation_in_m) = compute_deviation(&old_points, new_coords)
+        .combine(compute_deviation(&new_points, old_coords))
+        .result();
+
+    let line_distance = |line: &Line| -> f64 {
+        line.polylines
+            .iter()
+            .map(|points| compute_distance_of_polyline_in_metres(points))
+            .sum()
+    };
+    let old_distance_in_m = line_distance(old);
+    let new_distance_in_m = line_distance(new);
+
+    GeometryDiff {
+        max_deviation_in_m,
+        mean_deviation_in_m,
+        old_point_count: old.points().count(),
+        new_point_count: new.points().count(),
+        old_distance_in_m,
+        new_distance_in_m,
+        distance_delta_in_m: new_distance_in_m - old_distance_in_m,
+    }
+}
+
+/// Accumulated distances of points from a line.
+#[derive(Copy, Clone, Debug, Default)]
+struct Deviation {
+    max: f64,
+    sum: f64,
+    count: usize,
+}
+
+impl Deviation {
+    fn combine(self, other: Deviation) -> Deviation {
+        Deviation {
+            max: self.max.max(other.max),
+            sum: self.sum + other.sum,
+            count: self.count + other.count,
+        }
+    }
+
+    /// Returns the maximum and mean deviation.
+    fn result(self) -> (f64, f64) {
+        if self.count == 0 {
+            return (0.0, 0.0);
+        }
+        (self.max, self.sum / self.count as f64)
+    }
+}
+
+/// Computes the distance of each point from the closest segment of the polylines.
+fn compute_deviation(points: &[Coord], polylines: &[Vec<Coord>]) -> Deviation {
+    let mut deviation = Deviation::default();
+
+    if polylines.iter().all(|polyline| polyline.is_empty()) {
+        return deviation;
+    }
+
+    for &point in points {
+        let mut min_distance = f64::INFINITY;
+
+        for polyline in polylines {
+            if let [single] = polyline.as_slice() {
+                min_distance = min_distance.min(distance(point, *single));
+            }
+            for segment in polyline.windows(2) {
+                let (_, segment_distance) = closest_point_on_segment(point, segment[0], segment[1]);
+                min_distance = min_distance.min(segment_distance);
+            }
+        }
+
+        deviation.max = deviation.max.max(min_distance);
+        deviation.sum += min_distance;
+        deviation.count += 1;
+    }
+
+    deviation
+}
+
+/// Returns up to `MAX_MATCHING_SAMPLES` evenly spaced points of the polylines.
+fn sample(polylines: &[Vec<Coord>]) -> Vec<Coord> {
+    let points: Vec<Coord> = polylines.iter().flatten().copied().collect();
+    let step = points.len().div_ceil(MAX_MATCHING_SAMPLES).max(1);
+    points.into_iter().step_by(step).collect()
+}
+
+/// Matches the waypoints by name first, then by position.
+fn compare_waypoints(old: &[Waypoint], new: &[Waypoint], config: &DiffConfig) -> Vec<WaypointDiff> {
+    let mut new_matched = vec![false; new.len()];
+    let mut old_matched = vec![false; old.len()];
+    let mut pairs = Vec::new();
+
+    for (i, old_waypoint) in old.iter().enumerate() {
+        if old_waypoint.name.is_none() {
+            continue;
+        }
+        if let Some(j) =
+            (0..new.len()).find(|&j| !new_matched[j] && new[j].name == old_waypoint.name)
+        {
+            old_matched[i] = true;
+            new_matched[j] = true;
+            pairs.push((i, j));
+        }
+    }
+
+    for (i, old_waypoint) in old.iter().enumerate() {
+        if old_matched[i] {
+            continue;
+        }
+
+        let closest = (0..new.len())
+            .filter(|&j| !new_matched[j])
+            .map(|j| (j, Haversine.distance(old_waypoint.point(), new[j].point())))
+            .filter(|&(_, distance)| distance <= config.match_distance_in_m)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((j, _)) = closest {
+            old_matched[i] = true;
+            new_matched[j] = true;
+            pairs.push((i, j));
+        }
+    }
+
+    pairs.sort();
+
+    let mut diffs: Vec<WaypointDiff> = pairs
+        .into_iter()
+        .map(|(i, j)| {
+            let (old_waypoint, new_waypoint) = (&old[i], &new[j]);
+            let moved_by_in_m = Haversine.distance(old_waypoint.point(), new_waypoint.point());
+            let changes = compare_waypoint_attributes(old_waypoint, new_waypoint);
+
+            let change = if old_waypoint.name != new_waypoint.name {
+                ChangeKind::Renamed
+            } else if moved_by_in_m > 0.0 || !changes.is_empty() {
+                ChangeKind::Modified
+            } else {
+                ChangeKind::Unchanged
+            };
+
+            WaypointDiff {
+                change,
+                old_name: old_waypoint.name.clone(),
+                new_name: new_waypoint.name.clone(),
+                moved_by_in_m: Some(moved_by_in_m),
+                changes,
+            }
+        })
+        .collect();
+
+    for (_, waypoint) in old.iter().enumerate().filter(|(i, _)| !old_matched[*i]) {
+        diffs.push(WaypointDiff {
+            change: ChangeKind::Removed,
+            old_name: waypoint.name.clone(),
+            new_name: None,
+            moved_by_in_m: None,
+            changes: Vec::new(),
+        });
+    }
+
+    for (_, waypoint) in new.iter().enumerate().filter(|(j, _)| !new_matched[*j]) {
+        diffs.push(WaypointDiff {
+            change: ChangeKind::Added,
+            old_name: None,
+            new_name: waypoint.name.clone(),
+            moved_by_in_m: None,
+            changes: Vec::new(),
+        });
+    }
+
+    diffs
+}
+
+fn compare_waypoint_attributes(old: &Waypoint, new: &Waypoint) -> Vec<FieldChange> {
+    let fields = |waypoint: &Waypoint| -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("elevation", waypoint.elevation.map(|e| format!("{e:.1}"))),
+            ("time", waypoint.time.map(format_time)),
+            ("description", waypoint.description.clone()),
+            ("comment", waypoint.comment.clone()),
+            ("symbol", waypoint.symbol.clone()),
+            ("type", waypoint.type_.clone()),
+        ]
+    };
+
+    fields(old)
+        .into_iter()
+        .zip(fields(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange { field, old, new })
+        .collect()
+}
+
+fn format_time(time: gpx::Time) -> String {
+    let time = OffsetDateTime::from(time);
+    time.format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| time.to_string())
+}
+
+fn format_links(links: &[Link]) -> String {
+    links
+        .iter()
+        .map(|link| link.href.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
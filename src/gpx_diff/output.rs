@@ -0,0 +1,156 @@
+use super::differ::{ChangeKind, FieldChange, GpxDiff, LineDiff, WaypointDiff};
+use crate::error_messages;
+use anyhow::Context;
+use clap::ValueEnum;
+use std::io;
+use std::io::Write;
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON document containing all differences.
+    Json,
+}
+
+pub fn print_diff(diff: &GpxDiff, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            print_diff_as_text(diff, verbose);
+            Ok(())
+        }
+        OutputFormat::Json => print_diff_as_json(diff),
+    }
+}
+
+pub fn print_diff_as_json(diff: &GpxDiff) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    serde_json::to_writer_pretty(&mut stdout, diff)
+        .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+    writeln!(stdout).with_context(|| error_messages::STDOUT_WRITE_ERROR)
+}
+
+/// Prints the differences. Unchanged items are only listed if `verbose` is set.
+pub fn print_diff_as_text(diff: &GpxDiff, verbose: bool) {
+    if !diff.has_differences() && !verbose {
+        println!("No differences found.");
+        return;
+    }
+
+    if !diff.metadata.is_empty() {
+        println!("Metadata:");
+        print_field_changes(&diff.metadata, "    ");
+    }
+
+    print_line_diffs("Tracks", "Track", &diff.tracks, verbose);
+    print_line_diffs("Routes", "Route", &diff.routes, verbose);
+    print_waypoint_diffs(&diff.waypoints, verbose);
+}
+
+fn print_line_diffs(title: &str, kind: &str, diffs: &[LineDiff], verbose: bool) {
+    let diffs: Vec<&LineDiff> = diffs
+        .iter()
+        .filter(|diff| verbose || diff.change != ChangeKind::Unchanged)
+        .collect();
+
+    if diffs.is_empty() {
+        return;
+    }
+
+    println!("{title}:");
+
+    for diff in diffs {
+        let number = |index: Option<usize>| index.map(|i| i + 1).unwrap_or_default();
+        let old_name = format_name(&diff.old_name, kind, number(diff.old_index));
+        let new_name = format_name(&diff.new_name, kind, number(diff.new_index));
+
+        match diff.change {
+            ChangeKind::Added => println!("  + {new_name}"),
+            ChangeKind::Removed => println!("  - {old_name}"),
+            ChangeKind::Renamed => println!("  ~ {old_name} renamed to {new_name}"),
+            ChangeKind::Modified => println!("  ~ {new_name} modified"),
+            ChangeKind::Unchanged => println!("  = {new_name} unchanged"),
+        }
+
+        if let Some(geometry) = &diff.geometry
+            && (diff.change != ChangeKind::Unchanged || verbose)
+        {
+            println!(
+                "      Deviation: max. {:.1} m, mean {:.1} m",
+                geometry.max_deviation_in_m, geometry.mean_deviation_in_m
+            );
+            println!(
+                "      Distance: {:.2} km -> {:.2} km ({:+.0} m)",
+                geometry.old_distance_in_m / 1000.0,
+                geometry.new_distance_in_m / 1000.0,
+                geometry.distance_delta_in_m
+            );
+            println!(
+                "      Points: {} -> {}",
+                geometry.old_point_count, geometry.new_point_count
+            );
+        }
+    }
+}
+
+fn print_waypoint_diffs(diffs: &[WaypointDiff], verbose: bool) {
+    let diffs: Vec<&WaypointDiff> = diffs
+        .iter()
+        .filter(|diff| verbose || diff.change != ChangeKind::Unchanged)
+        .collect();
+
+    if diffs.is_empty() {
+        return;
+    }
+
+    println!("Waypoints:");
+
+    for diff in diffs {
+        let old_name = format_name(&diff.old_name, "Waypoint", 0);
+        let new_name = format_name(&diff.new_name, "Waypoint", 0);
+
+        match diff.change {
+            ChangeKind::Added => println!("  + {new_name}"),
+            ChangeKind::Removed => println!("  - {old_name}"),
+            ChangeKind::Renamed => println!("  ~ {old_name} renamed to {new_name}"),
+            ChangeKind::Modified => println!("  ~ {new_name} modified"),
+            ChangeKind::Unchanged => println!("  = {new_name} unchanged"),
+        }
+
+        if let Some(moved_by_in_m) = diff.moved_by_in_m
+            && moved_by_in_m > 0.0
+        {
+            println!("      Moved by {moved_by_in_m:.1} m");
+        }
+
+        print_field_changes(&diff.changes, "      ");
+    }
+}
+
+fn print_field_changes(changes: &[FieldChange], prefix: &str) {
+    for change in changes {
+        println!(
+            "{prefix}{}: {} -> {}",
+            change.field,
+            format_value(&change.old),
+            format_value(&change.new)
+        );
+    }
+}
+
+/// Formats the name in quotes, or "<kind> <number>" if it has no name.
+fn format_name(name: &Option<String>, kind: &str, number: usize) -> String {
+    match name {
+        Some(name) => format!("'{name}'"),
+        None if number > 0 => format!("{kind} {number}"),
+        None => format!("Unnamed {}", kind.to_lowercase()),
+    }
+}
+
+fn format_value(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("'{value}'"),
+        None => "(none)".to_string(),
+    }
+}
@@ -0,0 +1,156 @@
+use super::dem::ElevationModel;
+use super::elevation;
+use super::geoid::{self, GeoidModel, HeightConversion};
+use crate::gpx_cli_util;
+use crate::util;
+use clap::Parser;
+use gpx::Gpx;
+use log::{info, warn};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Input GPX file path.
+    input: PathBuf,
+
+    /// Output GPX file path.
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+
+    /// Quiet: Disable logging.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Elevation model: an SRTM tile (.hgt), a single-band GeoTIFF (.tif) in geographic
+    /// coordinates, or a directory containing such files. Can be specified multiple times.
+    #[arg(short = 'd', long = "dem", required_unless_present = "geoid")]
+    dem: Vec<PathBuf>,
+
+    /// Replace existing elevations. By default, only points without an elevation are updated.
+    #[arg(short = 'r', long = "replace")]
+    replace: bool,
+
+    /// Geoid grid with the height of mean sea level above the WGS 84 ellipsoid, either in the
+    /// ASCII format of the NGA (e.g. WW15MGH.GRD for EGM96) or as a GeoTIFF. Sets the geoid
+    /// height of all points. No geoid grid is bundled.
+    #[arg(short = 'g', long = "geoid")]
+    geoid: Option<PathBuf>,
+
+    /// Convert the elevations with the geoid grid. Elevation models such as SRTM use mean sea
+    /// level, so their elevations are set after converting to orthometric heights and before
+    /// converting to ellipsoidal heights.
+    #[arg(short = 'c', long = "convert", requires = "geoid")]
+    convert: Option<HeightConversion>,
+}
+
+pub fn run_cli() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_cli_with_args(args)
+}
+
+pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
+    util::setup_logging(args.quiet);
+
+    let mut model = if args.dem.is_empty() {
+        None
+    } else {
+        let model = ElevationModel::open(&args.dem)?;
+        info!("Found {} elevation model files.", model.tile_count());
+        Some(model)
+    };
+
+    let mut geoid_model = match &args.geoid {
+        Some(path) => Some(GeoidModel::open(path)?),
+        None => None,
+    };
+
+    // Elevations from the elevation model are orthometric heights, so they must be set while
+    // the file uses orthometric heights, too.
+    let dem_first = args.convert != Some(HeightConversion::ToOrthometric);
+
+    gpx_cli_util::read_and_write_gpx_file(args.input, args.output, |gpx| {
+        if dem_first && let Some(model) = &mut model {
+            set_elevation_from_dem(gpx, model, args.replace)?;
+        }
+
+        if let Some(geoid_model) = &mut geoid_model {
+            apply_geoid(gpx, geoid_model, args.convert)?;
+        }
+
+        if !dem_first && let Some(model) = &mut model {
+            set_elevation_from_dem(gpx, model, args.replace)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn apply_geoid(
+    gpx: &mut Gpx,
+    geoid_model: &mut GeoidModel,
+    conversion: Option<HeightConversion>,
+) -> anyhow::Result<()> {
+    info!("Looking up geoid heights...");
+    let report = geoid::apply_geoid(gpx, geoid_model, conversion)?;
+
+    info!(
+        "Set the geoid height of {} points.",
+        report.geoid_height_count
+    );
+    if conversion.is_some() {
+        info!(
+            "Converted the elevation of {} points.",
+            report.converted_count
+        );
+    }
+    if report.outside_count > 0 {
+        warn!(
+            "{} points are outside the geoid grid.",
+            report.outside_count
+        );
+    }
+
+    Ok(())
+}
+
+fn set_elevation_from_dem(
+    gpx: &mut Gpx,
+    model: &mut ElevationModel,
+    replace: bool,
+) -> anyhow::Result<()> {
+    info!("Looking up elevations...");
+    let report = elevation::set_elevation_from_dem(gpx, model, replace)?;
+
+    info!("Updated the elevation of {} points.", report.updated_count);
+    if report.kept_count > 0 {
+        info!(
+            "Kept the existing elevation of {} points.",
+            report.kept_count
+        );
+    }
+
+    if report.outside_count > 0 {
+        warn!(
+            "{} points are outside the elevation model files.",
+            report.outside_count
+        );
+        let tiles: Vec<&str> = report.missing_tiles.iter().map(String::as_str).collect();
+        warn!("Missing SRTM tiles: {}", tiles.join(", "));
+    }
+
+    if report.no_data_count > 0 {
+        warn!(
+            "{} points are in areas without data (e.g. voids).",
+            report.no_data_count
+        );
+    }
+
+    if !report.missing_by_item.is_empty() {
+        warn!("Points without elevation data:");
+        for (item, count) in &report.missing_by_item {
+            warn!("- {item}: {count} points");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,397 @@
+use crate::error_messages;
+use anyhow::{Context, bail};
+use geo::Point;
+use log::info;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tiff::ColorType;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+
+/// Value of void (missing) samples in SRTM tiles.
+const SRTM_VOID: f32 = -32768.0;
+
+const GEO_KEY_MODEL_TYPE: u16 = 1024;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const GEO_KEY_RASTER_TYPE: u16 = 1025;
+const RASTER_TYPE_PIXEL_IS_POINT: u16 = 2;
+const GEO_KEY_PROJECTED_CS_TYPE: u16 = 3072;
+
+/// Tolerance (in pixels) for points exactly on the border of a tile.
+const BORDER_TOLERANCE: f64 = 1e-6;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum DemFormat {
+    /// SRTM height file: a square grid of big-endian 16-bit integers covering 1° x 1°.
+    Hgt,
+    /// Single-band GeoTIFF in geographic coordinates.
+    GeoTiff,
+}
+
+/// Georeferencing of a grid of samples. Rows run from north to south.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Grid {
+    width: usize,
+    height: usize,
+    /// Longitude of the centre of the first column.
+    west: f64,
+    /// Latitude of the centre of the first row.
+    north: f64,
+    /// Size of a pixel in degrees.
+    pixel_width: f64,
+    pixel_height: f64,
+}
+
+impl Grid {
+    /// Returns the (fractional) column and row of the point, if it lies within the grid.
+    fn position_of(&self, point: Point) -> Option<(f64, f64)> {
+        let x = (point.x() - self.west) / self.pixel_width;
+        let y = (self.north - point.y()) / self.pixel_height;
+
+        let max_x = (self.width - 1) as f64;
+        let max_y = (self.height - 1) as f64;
+        let is_inside = (-BORDER_TOLERANCE..=max_x + BORDER_TOLERANCE).contains(&x)
+            && (-BORDER_TOLERANCE..=max_y + BORDER_TOLERANCE).contains(&y);
+
+        is_inside.then(|| (x.clamp(0.0, max_x), y.clamp(0.0, max_y)))
+    }
+}
+
+struct Raster {
+    values: Vec<f32>,
+    no_data: Option<f32>,
+}
+
+/// A DEM file. The samples are only loaded when a point within the tile is looked up.
+struct DemTile {
+    path: PathBuf,
+    format: DemFormat,
+    grid: Grid,
+    raster: Option<Raster>,
+}
+
+/// Digital elevation model made up of SRTM tiles and GeoTIFF files.
+pub struct ElevationModel {
+    tiles: Vec<DemTile>,
+}
+
+impl ElevationModel {
+    /// Opens the given DEM files, and all DEM files (".hgt", ".tif", ".tiff") in the given
+    /// directories.
+    pub fn open(paths: &[PathBuf]) -> anyhow::Result<ElevationModel> {
+        // Earlier paths take precedence where files overlap.
+        let mut files = Vec::new();
+
+        for path in paths {
+            if !path.exists() {
+                bail!(
+                    "{} ({})",
+                    error_messages::INPUT_PATH_NOT_FOUND,
+                    path.display()
+                );
+            }
+
+            if path.is_dir() {
+                let mut directory_files = Vec::new();
+                let entries =
+                    fs::read_dir(path).with_context(|| error_messages::DIRECTORY_READ_ERROR)?;
+                for entry in entries {
+                    let entry_path = entry
+                        .with_context(|| error_messages::DIRECTORY_READ_ERROR)?
+                        .path();
+                    if get_format(&entry_path).is_some() {
+                        directory_files.push(entry_path);
+                    }
+                }
+                directory_files.sort();
+                files.extend(directory_files);
+            } else {
+                files.push(path.clone());
+            }
+        }
+
+        let mut tiles = Vec::with_capacity(files.len());
+        for file in files {
+            let tile = match get_format(&file) {
+                Some(DemFormat::Hgt) => open_hgt_tile(&file),
+                Some(DemFormat::GeoTiff) => open_geotiff_tile(&file),
+                None => bail!(error_messages::UNSUPPORTED_DEM_FILE),
+            }
+            .with_context(|| {
+                format!(
+                    "{} ({})",
+                    error_messages::DEM_FILE_READ_ERROR,
+                    file.display()
+                )
+            })?;
+            tiles.push(tile);
+        }
+
+        if tiles.is_empty() {
+            bail!(error_messages::NO_DEM_FILES_FOUND);
+        }
+
+        Ok(ElevationModel { tiles })
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns whether any file covers the point, even if it has no data there.
+    pub fn covers(&self, point: Point) -> bool {
+        self.tiles
+            .iter()
+            .any(|tile| tile.grid.position_of(point).is_some())
+    }
+
+    /// Returns the elevation at the point, interpolated bilinearly between the surrounding
+    /// samples. Returns `None` if no tile covers the point or the tile has no data there.
+    pub fn elevation_at(&mut self, point: Point) -> anyhow::Result<Option<f64>> {
+        for tile in self.tiles.iter_mut() {
+            let Some((x, y)) = tile.grid.position_of(point) else {
+                continue;
+            };
+
+            if tile.raster.is_none() {
+                info!("Loading {}...", tile.path.display());
+                let raster = match tile.format {
+                    DemFormat::Hgt => read_hgt_raster(&tile.path),
+                    DemFormat::GeoTiff => read_geotiff_raster(&tile.path, &tile.grid),
+                }
+                .with_context(|| {
+                    format!(
+                        "{} ({})",
+                        error_messages::DEM_FILE_READ_ERROR,
+                        tile.path.display()
+                    )
+                })?;
+                tile.raster = Some(raster);
+            }
+
+            if let Some(raster) = &tile.raster
+                && let Some(elevation) = interpolate(raster, &tile.grid, x, y)
+            {
+                return Ok(Some(elevation));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Returns the name of the SRTM tile that contains the point, e.g. "N47E011".
+pub fn srtm_tile_name(point: Point) -> String {
+    let latitude = point.y().floor() as i32;
+    let longitude = point.x().floor() as i32;
+
+    format!(
+        "{}{:02}{}{:03}",
+        if latitude < 0 { 'S' } else { 'N' },
+        latitude.abs(),
+        if longitude < 0 { 'W' } else { 'E' },
+        longitude.abs()
+    )
+}
+
+fn get_format(path: &Path) -> Option<DemFormat> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+    match extension.as_str() {
+        "hgt" => Some(DemFormat::Hgt),
+        "tif" | "tiff" => Some(DemFormat::GeoTiff),
+        _ => None,
+    }
+}
+
+/// Bilinear interpolation. Samples without data are left out and the weights of the remaining
+/// samples are normalised.
+fn interpolate(raster: &Raster, grid: &Grid, x: f64, y: f64) -> Option<f64> {
+    let x0 = (x.floor() as usize).min(grid.width.saturating_sub(2));
+    let y0 = (y.floor() as usize).min(grid.height.saturating_sub(2));
+    let x1 = (x0 + 1).min(grid.width - 1);
+    let y1 = (y0 + 1).min(grid.height - 1);
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+    let samples = [
+        (x0, y0, (1.0 - tx) * (1.0 - ty)),
+        (x1, y0, tx * (1.0 - ty)),
+        (x0, y1, (1.0 - tx) * ty),
+        (x1, y1, tx * ty),
+    ];
+
+    let mut weighted_sum = 0.0;
+    let mut weight_sum = 0.0;
+
+    for (column, row, weight) in samples {
+        let value = raster.values[row * grid.width + column];
+        let is_valid = value.is_finite() && raster.no_data != Some(value);
+
+        if is_valid && weight > 0.0 {
+            weighted_sum += value as f64 * weight;
+            weight_sum += weight;
+        }
+    }
+
+    (weight_sum > 0.0).then(|| weighted_sum / weight_sum)
+}
+
+fn open_hgt_tile(path: &Path) -> anyhow::Result<DemTile> {
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+
+    let (Some(latitude), Some(longitude)) = (
+        parse_hgt_coordinate(name.get(0..3), 'N', 'S'),
+        parse_hgt_coordinate(name.get(3..7), 'E', 'W'),
+    ) else {
+        bail!(error_messages::INVALID_HGT_FILE_NAME);
+    };
+
+    let file_size = fs::metadata(path)?.len();
+    let size = (file_size as f64 / 2.0).sqrt() as usize;
+    if size < 2 || (size * size * 2) as u64 != file_size {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    }
+
+    let pixel_size = 1.0 / (size - 1) as f64;
+
+    Ok(DemTile {
+        path: path.to_path_buf(),
+        format: DemFormat::Hgt,
+        grid: Grid {
+            width: size,
+            height: size,
+            west: longitude as f64,
+            north: latitude as f64 + 1.0,
+            pixel_width: pixel_size,
+            pixel_height: pixel_size,
+        },
+        raster: None,
+    })
+}
+
+/// Parses the latitude ("N47") or longitude ("E011") part of an SRTM file name.
+fn parse_hgt_coordinate(text: Option<&str>, positive: char, negative: char) -> Option<i32> {
+    let text = text?;
+    let value: i32 = text.get(1..)?.parse().ok()?;
+
+    match text.chars().next()? {
+        c if c == positive => Some(value),
+        c if c == negative => Some(-value),
+        _ => None,
+    }
+}
+
+fn read_hgt_raster(path: &Path) -> anyhow::Result<Raster> {
+    let bytes = fs::read(path)?;
+    let values = bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_be_bytes([pair[0], pair[1]]) as f32)
+        .collect();
+
+    Ok(Raster {
+        values,
+        no_data: Some(SRTM_VOID),
+    })
+}
+
+fn open_geotiff_decoder(path: &Path) -> anyhow::Result<Decoder<BufReader<File>>> {
+    let file = File::open(path)?;
+    Ok(Decoder::new(BufReader::new(file))?)
+}
+
+fn open_geotiff_tile(path: &Path) -> anyhow::Result<DemTile> {
+    let mut decoder = open_geotiff_decoder(path)?;
+
+    if !matches!(decoder.colortype()?, ColorType::Gray(_)) {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    }
+
+    let (width, height) = decoder.dimensions()?;
+    let pixel_scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag)?;
+    let tie_point = decoder.get_tag_f64_vec(Tag::ModelTiepointTag)?;
+    let geo_keys = match decoder.find_tag(Tag::GeoKeyDirectoryTag)? {
+        Some(value) => value.into_u16_vec()?,
+        None => Vec::new(),
+    };
+
+    let model_type = find_geo_key(&geo_keys, GEO_KEY_MODEL_TYPE);
+    let is_projected = find_geo_key(&geo_keys, GEO_KEY_PROJECTED_CS_TYPE).is_some();
+    if is_projected || model_type.is_some_and(|model_type| model_type != MODEL_TYPE_GEOGRAPHIC) {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    }
+
+    let ([pixel_width, pixel_height, ..], [i, j, _, x, y, ..]) =
+        (pixel_scale.as_slice(), tie_point.as_slice())
+    else {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    };
+
+    // By default, the tie point refers to the corner of a pixel, not to its centre.
+    let is_pixel_point =
+        find_geo_key(&geo_keys, GEO_KEY_RASTER_TYPE) == Some(RASTER_TYPE_PIXEL_IS_POINT);
+    let centre_offset = if is_pixel_point { 0.0 } else { 0.5 };
+
+    if width < 2 || height < 2 || *pixel_width <= 0.0 || *pixel_height <= 0.0 {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    }
+
+    Ok(DemTile {
+        path: path.to_path_buf(),
+        format: DemFormat::GeoTiff,
+        grid: Grid {
+            width: width as usize,
+            height: height as usize,
+            west: x + (centre_offset - i) * pixel_width,
+            north: y - (centre_offset - j) * pixel_height,
+            pixel_width: *pixel_width,
+            pixel_height: *pixel_height,
+        },
+        raster: None,
+    })
+}
+
+/// Returns the value of a GeoKey that is stored directly in the GeoKey directory.
+fn find_geo_key(geo_keys: &[u16], key: u16) -> Option<u16> {
+    // The directory starts with a header of 4 values, followed by entries of 4 values:
+    // key, location (0 if the value is stored in the entry), count, and value.
+    geo_keys
+        .get(4..)?
+        .chunks_exact(4)
+        .find(|entry| entry[0] == key && entry[1] == 0)
+        .map(|entry| entry[3])
+}
+
+fn read_geotiff_raster(path: &Path, grid: &Grid) -> anyhow::Result<Raster> {
+    let mut decoder = open_geotiff_decoder(path)?;
+
+    let no_data = match decoder.find_tag(Tag::GdalNodata)? {
+        Some(value) => value.into_string()?.trim_matches('\0').trim().parse().ok(),
+        None => None,
+    };
+
+    let values: Vec<f32> = match decoder.read_image()? {
+        DecodingResult::U8(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::U16(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::U32(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::U64(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::F16(values) => values.into_iter().map(|v| v.to_f32()).collect(),
+        DecodingResult::F32(values) => values,
+        DecodingResult::F64(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I8(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I16(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I32(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I64(values) => values.into_iter().map(|v| v as f32).collect(),
+    };
+
+    if values.len() != grid.width * grid.height {
+        bail!(error_messages::UNSUPPORTED_DEM_FILE);
+    }
+
+    Ok(Raster { values, no_data })
+}
@@ -0,0 +1,103 @@
+use super::dem::{ElevationModel, srtm_tile_name};
+use crate::gpx_clean::precision::round_to_decimals;
+use gpx::{Gpx, Waypoint};
+use std::collections::BTreeSet;
+
+/// The elevation models are far less accurate, so more decimals would only be noise.
+const ELEVATION_DECIMALS: u32 = 2;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElevationReport {
+    /// Points whose elevation was set from the elevation model.
+    pub updated_count: usize,
+    /// Points that already had an elevation, which was kept.
+    pub kept_count: usize,
+    /// Points outside all elevation model files.
+    pub outside_count: usize,
+    /// Points within an elevation model file, which has no data there (e.g. voids in SRTM).
+    pub no_data_count: usize,
+    /// Names of the SRTM tiles that would cover the points outside the files, e.g. "N47E011".
+    pub missing_tiles: BTreeSet<String>,
+    /// Tracks, routes, and waypoints with points without elevation data, with their number.
+    pub missing_by_item: Vec<(String, usize)>,
+}
+
+/// Sets the elevation of all track points, route points, and waypoints from the elevation model.
+/// Existing elevations are only overwritten if `replace` is set.
+pub fn set_elevation_from_dem(
+    gpx: &mut Gpx,
+    model: &mut ElevationModel,
+    replace: bool,
+) -> anyhow::Result<ElevationReport> {
+    let mut report = ElevationReport::default();
+
+    for (i, track) in gpx.tracks.iter_mut().enumerate() {
+        let label = item_label("Track", i, &track.name);
+        let points = track
+            .segments
+            .iter_mut()
+            .flat_map(|segment| segment.points.iter_mut());
+        update_points(points, label, model, replace, &mut report)?;
+    }
+
+    for (i, route) in gpx.routes.iter_mut().enumerate() {
+        let label = item_label("Route", i, &route.name);
+        update_points(route.points.iter_mut(), label, model, replace, &mut report)?;
+    }
+
+    update_points(
+        gpx.waypoints.iter_mut(),
+        "Waypoints".to_string(),
+        model,
+        replace,
+        &mut report,
+    )?;
+
+    Ok(report)
+}
+
+fn update_points<'a>(
+    points: impl Iterator<Item = &'a mut Waypoint>,
+    label: String,
+    model: &mut ElevationModel,
+    replace: bool,
+    report: &mut ElevationReport,
+) -> anyhow::Result<()> {
+    let mut missing_count = 0;
+
+    for point in points {
+        if point.elevation.is_some() && !replace {
+            report.kept_count += 1;
+            continue;
+        }
+
+        match model.elevation_at(point.point())? {
+            Some(elevation) => {
+                point.elevation = Some(round_to_decimals(elevation, ELEVATION_DECIMALS));
+                report.updated_count += 1;
+            }
+            None if model.covers(point.point()) => {
+                missing_count += 1;
+                report.no_data_count += 1;
+            }
+            None => {
+                missing_count += 1;
+                report.outside_count += 1;
+                report.missing_tiles.insert(srtm_tile_name(point.point()));
+            }
+        }
+    }
+
+    if missing_count > 0 {
+        report.missing_by_item.push((label, missing_count));
+    }
+
+    Ok(())
+}
+
+fn item_label(kind: &str, index: usize, name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("{kind} {} '{name}'", index + 1),
+        None => format!("{kind} {}", index + 1),
+    }
+}
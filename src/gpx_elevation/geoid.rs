@@ -0,0 +1,229 @@
+use super::dem::ElevationModel;
+use crate::error_messages;
+use crate::gpx_clean::precision::round_to_decimals;
+use anyhow::{Context, bail};
+use clap::ValueEnum;
+use geo::Point;
+use gpx::{Gpx, Waypoint};
+use std::fs;
+use std::path::Path;
+
+/// The geoid models are accurate to a few decimetres at best.
+const GEOID_HEIGHT_DECIMALS: u32 = 2;
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HeightConversion {
+    /// Convert heights above the WGS 84 ellipsoid to heights above mean sea level.
+    ToOrthometric,
+    /// Convert heights above mean sea level to heights above the WGS 84 ellipsoid.
+    ToEllipsoidal,
+}
+
+/// Geoid heights (undulations) in the ASCII grid format of the NGA, as used by WW15MGH.GRD for
+/// EGM96. The header contains the south, north, west, and east bounds, and the latitude and
+/// longitude spacing, all in degrees. It is followed by the values row by row from north to
+/// south and west to east.
+struct GeoidGrid {
+    north: f64,
+    west: f64,
+    latitude_step: f64,
+    longitude_step: f64,
+    rows: usize,
+    columns: usize,
+    /// Whether the grid spans all longitudes, so that it wraps around.
+    is_global: bool,
+    values: Vec<f64>,
+}
+
+impl GeoidGrid {
+    fn parse(text: &str) -> Option<GeoidGrid> {
+        let mut numbers = text.split_whitespace().map(|number| number.parse::<f64>());
+        let mut header = [0.0; 6];
+        for value in header.iter_mut() {
+            *value = numbers.next()?.ok()?;
+        }
+
+        let [south, north, west, east, latitude_step, longitude_step] = header;
+        if latitude_step <= 0.0 || longitude_step <= 0.0 || north <= south || east <= west {
+            return None;
+        }
+
+        let rows = ((north - south) / latitude_step).round() as usize + 1;
+        let columns = ((east - west) / longitude_step).round() as usize + 1;
+        let values = numbers.collect::<Result<Vec<f64>, _>>().ok()?;
+
+        if values.len() != rows * columns {
+            return None;
+        }
+
+        Some(GeoidGrid {
+            north,
+            west,
+            latitude_step,
+            longitude_step,
+            rows,
+            columns,
+            is_global: east - west >= 360.0 - longitude_step / 2.0,
+            values,
+        })
+    }
+
+    fn undulation_at(&self, point: Point) -> Option<f64> {
+        let longitude = if self.is_global {
+            self.west + (point.x() - self.west).rem_euclid(360.0)
+        } else {
+            point.x()
+        };
+
+        let x = (longitude - self.west) / self.longitude_step;
+        let y = (self.north - point.y()) / self.latitude_step;
+        let (max_x, max_y) = ((self.columns - 1) as f64, (self.rows - 1) as f64);
+
+        if !(0.0..=max_x).contains(&x) || !(0.0..=max_y).contains(&y) {
+            return None;
+        }
+
+        let x0 = (x.floor() as usize).min(self.columns.saturating_sub(2));
+        let y0 = (y.floor() as usize).min(self.rows.saturating_sub(2));
+        let x1 = (x0 + 1).min(self.columns - 1);
+        let y1 = (y0 + 1).min(self.rows - 1);
+        let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+        let value = |column: usize, row: usize| self.values[row * self.columns + column];
+
+        let top = value(x0, y0) * (1.0 - tx) + value(x1, y0) * tx;
+        let bottom = value(x0, y1) * (1.0 - tx) + value(x1, y1) * tx;
+
+        Some(top * (1.0 - ty) + bottom * ty)
+    }
+}
+
+enum GeoidSource {
+    Grid(GeoidGrid),
+    /// Single-band GeoTIFF in geographic coordinates, e.g. a geoid grid from PROJ.
+    GeoTiff(ElevationModel),
+}
+
+/// Geoid model that provides the height of the geoid (mean sea level) above the WGS 84
+/// ellipsoid. No geoid data is bundled, so it has to be loaded from a grid file.
+pub struct GeoidModel {
+    source: GeoidSource,
+}
+
+impl GeoidModel {
+    /// Opens an NGA ASCII grid (".grd") or a GeoTIFF (".tif", ".tiff") with geoid heights.
+    pub fn open(path: &Path) -> anyhow::Result<GeoidModel> {
+        if !path.exists() {
+            bail!(
+                "{} ({})",
+                error_messages::INPUT_PATH_NOT_FOUND,
+                path.display()
+            );
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if extension == "tif" || extension == "tiff" {
+            let model = ElevationModel::open(&[path.to_path_buf()])?;
+            return Ok(GeoidModel {
+                source: GeoidSource::GeoTiff(model),
+            });
+        }
+
+        let text =
+            fs::read_to_string(path).with_context(|| error_messages::GEOID_FILE_READ_ERROR)?;
+        let grid = GeoidGrid::parse(&text).with_context(|| error_messages::INVALID_GEOID_GRID)?;
+
+        Ok(GeoidModel {
+            source: GeoidSource::Grid(grid),
+        })
+    }
+
+    /// Returns the height of the geoid above the ellipsoid at the point.
+    pub fn undulation_at(&mut self, point: Point) -> anyhow::Result<Option<f64>> {
+        match &mut self.source {
+            GeoidSource::Grid(grid) => Ok(grid.undulation_at(point)),
+            GeoidSource::GeoTiff(model) => {
+                // Global grids may use longitudes from 0 to 360 instead of -180 to 180.
+                for longitude_offset in [0.0, 360.0, -360.0] {
+                    let shifted = Point::new(point.x() + longitude_offset, point.y());
+                    if let Some(undulation) = model.elevation_at(shifted)? {
+                        return Ok(Some(undulation));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct GeoidReport {
+    /// Points whose geoid height was set.
+    pub geoid_height_count: usize,
+    /// Points whose elevation was converted.
+    pub converted_count: usize,
+    /// Points outside the geoid grid.
+    pub outside_count: usize,
+}
+
+/// Sets the geoid height of all track points, route points, and waypoints, and optionally
+/// converts their elevations between ellipsoidal and orthometric heights.
+pub fn apply_geoid(
+    gpx: &mut Gpx,
+    model: &mut GeoidModel,
+    conversion: Option<HeightConversion>,
+) -> anyhow::Result<GeoidReport> {
+    let mut report = GeoidReport::default();
+
+    let track_points = gpx
+        .tracks
+        .iter_mut()
+        .flat_map(|track| track.segments.iter_mut())
+        .flat_map(|segment| segment.points.iter_mut());
+    let route_points = gpx
+        .routes
+        .iter_mut()
+        .flat_map(|route| route.points.iter_mut());
+    let points = gpx
+        .waypoints
+        .iter_mut()
+        .chain(track_points)
+        .chain(route_points);
+
+    for point in points {
+        apply_geoid_to_point(point, model, conversion, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+fn apply_geoid_to_point(
+    point: &mut Waypoint,
+    model: &mut GeoidModel,
+    conversion: Option<HeightConversion>,
+    report: &mut GeoidReport,
+) -> anyhow::Result<()> {
+    let Some(undulation) = model.undulation_at(point.point())? else {
+        report.outside_count += 1;
+        return Ok(());
+    };
+
+    point.geoidheight = Some(round_to_decimals(undulation, GEOID_HEIGHT_DECIMALS));
+    report.geoid_height_count += 1;
+
+    if let (Some(conversion), Some(elevation)) = (conversion, point.elevation) {
+        // Ellipsoidal height = orthometric height + geoid height.
+        let converted = match conversion {
+            HeightConversion::ToOrthometric => elevation - undulation,
+            HeightConversion::ToEllipsoidal => elevation + undulation,
+        };
+        point.elevation = Some(converted);
+        report.converted_count += 1;
+    }
+
+    Ok(())
+}
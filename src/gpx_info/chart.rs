@@ -0,0 +1,226 @@
+use super::stats::ProfilePoint;
+use clap::ValueEnum;
+use terminal_size::{Width, terminal_size};
+
+pub const DEFAULT_CHART_HEIGHT: usize = 10;
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+const MIN_CHART_COLUMNS: usize = 10;
+
+/// Unicode block characters from 1/8 to 8/8 of a cell height.
+const UNICODE_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChartStyle {
+    /// Unicode block characters with a resolution of 1/8 cell.
+    Unicode,
+    /// Plain ASCII characters.
+    Ascii,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartConfig {
+    /// Total width of the chart in characters, including the axis labels.
+    pub width: usize,
+    /// Height of the plot area in lines.
+    pub height: usize,
+    pub style: ChartStyle,
+}
+
+/// Returns the width of the terminal, or a default width if it cannot be determined
+/// (e.g. when the output is redirected to a file).
+pub fn get_terminal_width() -> usize {
+    if let Some((Width(width), _)) = terminal_size() {
+        return width as usize;
+    }
+
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Renders an elevation-vs-distance chart. Returns `None` if the profile has no elevation data.
+pub fn render_elevation_chart(
+    profile: &[ProfilePoint],
+    config: &ChartConfig,
+) -> Option<Vec<String>> {
+    let points: Vec<(f64, f64)> = profile
+        .iter()
+        .filter_map(|point| Some((point.distance_in_m, point.elevation_in_m?)))
+        .collect();
+
+    let first = points.first()?;
+    let last = points.last()?;
+
+    let mut min_elevation = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let mut max_elevation = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    if max_elevation - min_elevation < 1.0 {
+        min_elevation -= 1.0;
+        max_elevation += 1.0;
+    }
+
+    let labels = [
+        format!("{max_elevation:.0} m"),
+        format!("{:.0} m", (min_elevation + max_elevation) / 2.0),
+        format!("{min_elevation:.0} m"),
+    ];
+    let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+
+    let height = config.height.max(2);
+    let columns = config
+        .width
+        .saturating_sub(label_width + 2)
+        .max(MIN_CHART_COLUMNS);
+
+    let column_elevations = compute_column_elevations(&points, first.0, last.0, columns);
+
+    let sub_levels = match config.style {
+        ChartStyle::Unicode => UNICODE_BLOCKS.len(),
+        ChartStyle::Ascii => 1,
+    };
+
+    // Height of each column in sub-levels. Every column is at least one sub-level high.
+    let column_heights: Vec<usize> = column_elevations
+        .iter()
+        .map(|elevation| {
+            let relative = (elevation - min_elevation) / (max_elevation - min_elevation);
+            ((relative * (height * sub_levels) as f64).round() as usize).max(1)
+        })
+        .collect();
+
+    let (axis, labelled_axis, corner, horizontal) = match config.style {
+        ChartStyle::Unicode => ('│', '┤', '└', '─'),
+        ChartStyle::Ascii => ('|', '+', '+', '-'),
+    };
+
+    let mut lines = Vec::with_capacity(height + 2);
+
+    for row in 0..height {
+        let level = height - 1 - row;
+
+        let label = match row {
+            0 => labels[0].as_str(),
+            r if r == height / 2 && height > 2 => labels[1].as_str(),
+            r if r == height - 1 => labels[2].as_str(),
+            _ => "",
+        };
+        let axis_char = if label.is_empty() {
+            axis
+        } else {
+            labelled_axis
+        };
+
+        let cells: String = column_heights
+            .iter()
+            .map(|column_height| {
+                let full_levels = column_height / sub_levels;
+                let remainder = column_height % sub_levels;
+
+                if full_levels > level {
+                    full_cell(config.style)
+                } else if full_levels == level && remainder > 0 {
+                    UNICODE_BLOCKS[remainder - 1]
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+
+        lines.push(format!(
+            "{label:>label_width$} {axis_char}{}",
+            cells.trim_end()
+        ));
+    }
+
+    let x_axis: String = std::iter::repeat_n(horizontal, columns).collect();
+    lines.push(format!("{:label_width$} {corner}{x_axis}", ""));
+
+    lines.push(format!(
+        "{:label_width$}  {}",
+        "",
+        format_distance_labels(first.0, last.0, columns)
+    ));
+
+    Some(lines)
+}
+
+fn full_cell(style: ChartStyle) -> char {
+    match style {
+        ChartStyle::Unicode => UNICODE_BLOCKS[UNICODE_BLOCKS.len() - 1],
+        ChartStyle::Ascii => '#',
+    }
+}
+
+/// Computes the elevation for each column. Uses the highest point within the column,
+/// or the interpolated elevation if no point lies within it.
+fn compute_column_elevations(
+    points: &[(f64, f64)],
+    start_distance: f64,
+    end_distance: f64,
+    columns: usize,
+) -> Vec<f64> {
+    let column_width = (end_distance - start_distance) / columns as f64;
+    let mut i = 0;
+
+    (0..columns)
+        .map(|column| {
+            let from = start_distance + column as f64 * column_width;
+            let to = from + column_width;
+            let centre = (from + to) / 2.0;
+
+            while i + 1 < points.len() && points[i + 1].0 < from {
+                i += 1;
+            }
+
+            let mut elevation = interpolate_elevation(points, i, centre);
+
+            for point in points[i..].iter().take_while(|point| point.0 <= to) {
+                if point.0 >= from {
+                    elevation = elevation.max(point.1);
+                }
+            }
+
+            elevation
+        })
+        .collect()
+}
+
+fn interpolate_elevation(points: &[(f64, f64)], i: usize, distance: f64) -> f64 {
+    let a = points[i];
+    let Some(&b) = points.get(i + 1) else {
+        return a.1;
+    };
+
+    if b.0 <= a.0 {
+        return b.1;
+    }
+
+    let t = ((distance - a.0) / (b.0 - a.0)).clamp(0.0, 1.0);
+    a.1 + (b.1 - a.1) * t
+}
+
+/// Creates the distance labels below the x-axis: start, middle and end.
+fn format_distance_labels(start_distance: f64, end_distance: f64, columns: usize) -> String {
+    let start = format!("{:.1} km", start_distance / 1000.0);
+    let middle = format!("{:.1} km", (start_distance + end_distance) / 2000.0);
+    let end = format!("{:.1} km", end_distance / 1000.0);
+
+    let mut line: Vec<char> = vec![' '; columns];
+
+    let mut place = |text: &str, position: usize| {
+        let position = position.min(columns.saturating_sub(text.len()));
+        for (offset, c) in text.chars().enumerate() {
+            if let Some(cell) = line.get_mut(position + offset) {
+                *cell = c;
+            }
+        }
+    };
+
+    place(&end, columns.saturating_sub(end.len()));
+    if columns >= start.len() + middle.len() + end.len() + 4 {
+        place(&middle, (columns - middle.len()) / 2);
+    }
+    place(&start, 0);
+
+    line.into_iter().collect::<String>().trim_end().to_string()
+}
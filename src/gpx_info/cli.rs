@@ -1,15 +1,81 @@
+use super::chart;
+use super::chart::{ChartConfig, ChartStyle, DEFAULT_CHART_HEIGHT};
+use super::climbs::{
+    ClimbConfig, DEFAULT_CLIMB_MAX_DIP, DEFAULT_CLIMB_MIN_GAIN, DEFAULT_CLIMB_MIN_LENGTH,
+    DEFAULT_CLIMB_SMOOTHING_WINDOW,
+};
 use super::info;
+use super::output;
+use super::output::{OutputFormat, OutputOptions};
+use super::splits;
+use super::splits::SplitConfig;
+use super::stats::{DEFAULT_ELEVATION_THRESHOLD, DEFAULT_MOVING_SPEED_THRESHOLD, StatsConfig};
+use crate::{error_messages, gpx_cli_util};
+use anyhow::anyhow;
 use clap::Parser;
 use std::path::PathBuf;
 
 #[derive(Parser)]
 pub struct Args {
-    /// Input GPX file path.
-    input: PathBuf,
+    /// Input GPX files, directories, or glob patterns (e.g. "tracks/*.gpx").
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Search directories recursively for GPX files.
+    #[arg(short = 'r', long = "recursive")]
+    recursive: bool,
 
     /// Display additional information.
     #[arg(short = 'v', long = "verbose")]
     verbose: bool,
+
+    /// Output format.
+    #[arg(short = 'f', long = "format", default_value = "text")]
+    format: OutputFormat,
+
+    /// Minimum elevation change (in metres) counted towards ascent / descent.
+    #[arg(long = "elevation-threshold", default_value_t = DEFAULT_ELEVATION_THRESHOLD)]
+    elevation_threshold: f64,
+
+    /// Minimum speed (in m/s) at which the time between two points counts as moving.
+    #[arg(long = "moving-speed", default_value_t = DEFAULT_MOVING_SPEED_THRESHOLD)]
+    moving_speed_threshold: f64,
+
+    /// Print a split table for each track, e.g. "1km", "1mi" or "400m".
+    #[arg(long = "splits", value_parser = splits::parse_split_config)]
+    splits: Option<SplitConfig>,
+
+    /// Detect and categorise climbs in each track and route.
+    #[arg(long = "climbs")]
+    climbs: bool,
+
+    /// Minimum elevation gain (in metres) of a climb.
+    #[arg(long = "climb-min-gain", default_value_t = DEFAULT_CLIMB_MIN_GAIN)]
+    climb_min_gain: f64,
+
+    /// Minimum length (in metres) of a climb.
+    #[arg(long = "climb-min-length", default_value_t = DEFAULT_CLIMB_MIN_LENGTH)]
+    climb_min_length: f64,
+
+    /// Maximum descent (in metres) allowed within a climb.
+    #[arg(long = "climb-max-dip", default_value_t = DEFAULT_CLIMB_MAX_DIP)]
+    climb_max_dip: f64,
+
+    /// Window (in metres) for smoothing the elevation profile before detecting climbs.
+    #[arg(long = "climb-smoothing", default_value_t = DEFAULT_CLIMB_SMOOTHING_WINDOW)]
+    climb_smoothing: f64,
+
+    /// Draw an elevation profile chart for each track and route.
+    #[arg(long = "profile")]
+    profile: bool,
+
+    /// Height of the elevation profile chart in lines.
+    #[arg(long = "profile-height", default_value_t = DEFAULT_CHART_HEIGHT)]
+    profile_height: usize,
+
+    /// Characters used to draw the elevation profile chart.
+    #[arg(long = "profile-style", default_value = "unicode")]
+    profile_style: ChartStyle,
 }
 
 pub fn run_cli() -> anyhow::Result<()> {
@@ -18,5 +84,59 @@ pub fn run_cli() -> anyhow::Result<()> {
 }
 
 pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
-    info::print_gpx_file_info(&args.input, args.verbose)
+    let csv_table_count = [args.splits.is_some(), args.climbs, args.profile]
+        .iter()
+        .filter(|requested| **requested)
+        .count();
+
+    if args.format == OutputFormat::Csv && csv_table_count > 1 {
+        return Err(anyhow!(
+            "CSV output supports only one of --splits, --climbs and --profile at a time."
+        ));
+    }
+
+    let climb_config = ClimbConfig {
+        min_gain_in_m: args.climb_min_gain,
+        min_length_in_m: args.climb_min_length,
+        max_dip_in_m: args.climb_max_dip,
+        smoothing_window_in_m: args.climb_smoothing,
+    };
+
+    let config = StatsConfig {
+        elevation_threshold: args.elevation_threshold,
+        moving_speed_threshold: args.moving_speed_threshold,
+        splits: args.splits,
+        climbs: args.climbs.then_some(climb_config),
+        include_profile: args.profile,
+    };
+
+    let input_paths = gpx_cli_util::collect_input_files(&args.inputs, args.recursive)?;
+
+    let collection_info = info::read_gpx_files_info(&input_paths, &config);
+
+    for skipped_file in &collection_info.skipped_files {
+        eprintln!(
+            "Warning: Skipping '{}': {}",
+            skipped_file.path.display(),
+            skipped_file.error
+        );
+    }
+
+    if collection_info.files.is_empty() {
+        return Err(anyhow!(error_messages::NO_READABLE_INPUT_FILES));
+    }
+
+    let options = OutputOptions {
+        format: args.format,
+        verbose: args.verbose,
+        split_unit: args.splits.map(|split_config| split_config.unit),
+        show_climbs: args.climbs,
+        chart: args.profile.then(|| ChartConfig {
+            width: chart::get_terminal_width(),
+            height: args.profile_height,
+            style: args.profile_style,
+        }),
+    };
+
+    output::print_collection_info(&collection_info, &options)
 }
@@ -0,0 +1,226 @@
+use super::stats::ProfilePoint;
+use serde::Serialize;
+
+pub const DEFAULT_CLIMB_MIN_GAIN: f64 = 20.0;
+pub const DEFAULT_CLIMB_MIN_LENGTH: f64 = 300.0;
+pub const DEFAULT_CLIMB_MAX_DIP: f64 = 10.0;
+pub const DEFAULT_CLIMB_SMOOTHING_WINDOW: f64 = 100.0;
+
+/// Distance between the samples of the resampled elevation profile.
+const RESAMPLING_STEP_IN_M: f64 = 10.0;
+/// Length of the window over which the maximum gradient is measured.
+const MAX_GRADIENT_WINDOW_IN_M: f64 = 100.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClimbConfig {
+    /// Minimum elevation gain (in metres) of a climb.
+    pub min_gain_in_m: f64,
+    /// Minimum length (in metres) of a climb.
+    pub min_length_in_m: f64,
+    /// Maximum descent (in metres) within a climb before it is considered finished.
+    pub max_dip_in_m: f64,
+    /// Width (in metres) of the moving average applied to the elevation profile.
+    pub smoothing_window_in_m: f64,
+}
+
+/// Categories based on the climb score (length in metres * average gradient in percent),
+/// similar to the categories used by common cycling platforms.
+#[derive(Serialize, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClimbCategory {
+    #[serde(rename = "4")]
+    Cat4,
+    #[serde(rename = "3")]
+    Cat3,
+    #[serde(rename = "2")]
+    Cat2,
+    #[serde(rename = "1")]
+    Cat1,
+    #[serde(rename = "HC")]
+    HorsCategorie,
+}
+
+impl ClimbCategory {
+    /// Minimum average gradient (in percent) for a climb to be categorised.
+    const MIN_GRADIENT_PERCENT: f64 = 3.0;
+
+    pub fn from_climb(length_in_m: f64, average_gradient_percent: f64) -> Option<Self> {
+        if average_gradient_percent < Self::MIN_GRADIENT_PERCENT {
+            return None;
+        }
+
+        let score = length_in_m * average_gradient_percent;
+
+        match score {
+            s if s >= 80000.0 => Some(ClimbCategory::HorsCategorie),
+            s if s >= 64000.0 => Some(ClimbCategory::Cat1),
+            s if s >= 32000.0 => Some(ClimbCategory::Cat2),
+            s if s >= 16000.0 => Some(ClimbCategory::Cat3),
+            s if s >= 8000.0 => Some(ClimbCategory::Cat4),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClimbCategory::Cat4 => "4",
+            ClimbCategory::Cat3 => "3",
+            ClimbCategory::Cat2 => "2",
+            ClimbCategory::Cat1 => "1",
+            ClimbCategory::HorsCategorie => "HC",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Climb {
+    pub start_distance_in_m: f64,
+    pub end_distance_in_m: f64,
+    pub length_in_m: f64,
+    pub start_elevation_in_m: f64,
+    pub end_elevation_in_m: f64,
+    pub gain_in_m: f64,
+    pub average_gradient_percent: f64,
+    pub max_gradient_percent: f64,
+    pub category: Option<ClimbCategory>,
+}
+
+/// Detects contiguous climbs in the elevation profile. Points without elevation are ignored.
+pub fn detect_climbs(profile: &[ProfilePoint], config: &ClimbConfig) -> Vec<Climb> {
+    let samples = resample_elevation_profile(profile, RESAMPLING_STEP_IN_M);
+    let elevations = smooth_elevations(&samples, config.smoothing_window_in_m);
+
+    let mut climbs = Vec::new();
+
+    if samples.is_empty() {
+        return climbs;
+    }
+
+    let mut start = 0;
+    let mut peak = 0;
+
+    for i in 1..samples.len() {
+        if elevations[i] > elevations[peak] {
+            peak = i;
+        } else if elevations[peak] - elevations[i] > config.max_dip_in_m
+            || elevations[i] < elevations[start]
+        {
+            climbs.extend(create_climb(&samples, &elevations, start, peak, config));
+            start = i;
+            peak = i;
+        }
+    }
+
+    climbs.extend(create_climb(&samples, &elevations, start, peak, config));
+
+    climbs
+}
+
+/// Returns the climb between the `start` and `end` sample if it satisfies the thresholds.
+fn create_climb(
+    samples: &[(f64, f64)],
+    elevations: &[f64],
+    start: usize,
+    end: usize,
+    config: &ClimbConfig,
+) -> Option<Climb> {
+    let start_distance_in_m = samples[start].0;
+    let end_distance_in_m = samples[end].0;
+    let length_in_m = end_distance_in_m - start_distance_in_m;
+    let gain_in_m = elevations[end] - elevations[start];
+
+    if length_in_m < config.min_length_in_m || gain_in_m < config.min_gain_in_m {
+        return None;
+    }
+
+    let average_gradient_percent = gain_in_m / length_in_m * 100.0;
+
+    Some(Climb {
+        start_distance_in_m,
+        end_distance_in_m,
+        length_in_m,
+        start_elevation_in_m: elevations[start],
+        end_elevation_in_m: elevations[end],
+        gain_in_m,
+        average_gradient_percent,
+        max_gradient_percent: compute_max_gradient(samples, elevations, start, end)
+            .max(average_gradient_percent),
+        category: ClimbCategory::from_climb(length_in_m, average_gradient_percent),
+    })
+}
+
+fn compute_max_gradient(
+    samples: &[(f64, f64)],
+    elevations: &[f64],
+    start: usize,
+    end: usize,
+) -> f64 {
+    let mut max_gradient_percent = f64::MIN;
+    let mut window_start = start;
+
+    for window_end in start + 1..=end {
+        while samples[window_end].0 - samples[window_start + 1].0 >= MAX_GRADIENT_WINDOW_IN_M {
+            window_start += 1;
+        }
+
+        let length = samples[window_end].0 - samples[window_start].0;
+        if length >= MAX_GRADIENT_WINDOW_IN_M {
+            let gradient = (elevations[window_end] - elevations[window_start]) / length * 100.0;
+            max_gradient_percent = max_gradient_percent.max(gradient);
+        }
+    }
+
+    max_gradient_percent
+}
+
+/// Resamples the profile at evenly spaced distances. Returns (distance, elevation) pairs.
+fn resample_elevation_profile(profile: &[ProfilePoint], step_in_m: f64) -> Vec<(f64, f64)> {
+    let points: Vec<(f64, f64)> = profile
+        .iter()
+        .filter_map(|point| Some((point.distance_in_m, point.elevation_in_m?)))
+        .collect();
+
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+
+    let mut samples = Vec::new();
+    let mut distance = first.0;
+    let mut i = 1;
+
+    while distance <= last.0 {
+        while i < points.len() - 1 && points[i].0 < distance {
+            i += 1;
+        }
+
+        let elevation = if points.len() == 1 {
+            first.1
+        } else {
+            let (a, b) = (points[i - 1], points[i]);
+            let t = if b.0 > a.0 {
+                ((distance - a.0) / (b.0 - a.0)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            a.1 + (b.1 - a.1) * t
+        };
+
+        samples.push((distance, elevation));
+        distance += step_in_m;
+    }
+
+    samples
+}
+
+/// Applies a centred moving average with the given window width (in metres).
+fn smooth_elevations(samples: &[(f64, f64)], window_in_m: f64) -> Vec<f64> {
+    let half_window = (window_in_m / 2.0 / RESAMPLING_STEP_IN_M).round() as usize;
+
+    (0..samples.len())
+        .map(|i| {
+            let from = i.saturating_sub(half_window);
+            let to = (i + half_window).min(samples.len() - 1);
+            let window = &samples[from..=to];
+            window.iter().map(|(_, elevation)| elevation).sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
@@ -1,138 +1,280 @@
+use super::climbs::Climb;
+use super::splits::Split;
+use super::stats::{ElevationStats, ProfilePoint, StatsConfig, TimeStats};
+use super::{climbs, splits, stats};
 use crate::error_messages;
 use anyhow::Context;
 use geo::{Distance, Haversine};
 use gpx::{Gpx, Route, Track, Waypoint};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
-
-pub fn print_gpx_file_info(path: &Path, verbose: bool) -> anyhow::Result<()> {
-    let input_file_contents =
-        fs::read(path).with_context(|| error_messages::INPUT_FILE_READ_ERROR)?;
-
-    let file_name = path
-        .file_name()
-        .with_context(|| error_messages::INPUT_PATH_MISSING_FILE_NAME)?;
-
-    println!("File: {}", file_name.display());
-
-    if verbose {
-        let size_in_kb = input_file_contents.len() as f64 / 1000.0;
-        println!("Size: {:.1} KB", size_in_kb);
-    }
-
-    let gpx = gpx::read(input_file_contents.as_slice())
-        .with_context(|| error_messages::GPX_PARSE_ERROR)?;
-
-    println!();
-
-    print_gpx_info(&gpx, verbose);
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FileInfo {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub size_in_bytes: usize,
+    pub waypoints: Vec<WaypointInfo>,
+    pub tracks: Vec<TrackInfo>,
+    pub routes: Vec<RouteInfo>,
+}
 
-    Ok(())
+#[derive(Serialize, Clone, Debug)]
+pub struct WaypointInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation_in_m: Option<f64>,
 }
 
-pub fn print_gpx_info(gpx: &Gpx, verbose: bool) {
-    print_all_waypoints_info(&gpx.waypoints, verbose);
+#[derive(Serialize, Clone, Debug)]
+pub struct TrackInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub segment_count: usize,
+    pub point_count: usize,
+    pub distance_in_m: f64,
+    pub elevation: Option<ElevationStats>,
+    pub time: Option<TimeStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub splits: Option<Vec<Split>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub climbs: Option<Vec<Climb>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Vec<ProfilePoint>>,
+}
 
-    print_all_tracks_info(&gpx.tracks, verbose);
+#[derive(Serialize, Clone, Debug)]
+pub struct RouteInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub point_count: usize,
+    pub distance_in_m: f64,
+    pub elevation: Option<ElevationStats>,
+    pub time: Option<TimeStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub climbs: Option<Vec<Climb>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Vec<ProfilePoint>>,
+}
 
-    print_all_routes_info(&gpx.routes, verbose);
+/// Aggregated totals over one or more files.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Summary {
+    pub file_count: usize,
+    pub track_count: usize,
+    pub route_count: usize,
+    pub waypoint_count: usize,
+    pub distance_in_m: f64,
+    pub ascent_in_m: f64,
+    pub descent_in_m: f64,
+    pub duration_in_s: f64,
+    pub moving_time_in_s: f64,
 }
 
-pub fn print_all_tracks_info(tracks: &Vec<Track>, verbose: bool) {
-    if tracks.is_empty() {
-        return;
+impl Summary {
+    pub fn add(&mut self, other: &Summary) {
+        self.file_count += other.file_count;
+        self.track_count += other.track_count;
+        self.route_count += other.route_count;
+        self.waypoint_count += other.waypoint_count;
+        self.distance_in_m += other.distance_in_m;
+        self.ascent_in_m += other.ascent_in_m;
+        self.descent_in_m += other.descent_in_m;
+        self.duration_in_s += other.duration_in_s;
+        self.moving_time_in_s += other.moving_time_in_s;
     }
+}
 
-    println!("Tracks: {}", tracks.len());
-
-    for track in tracks {
-        print_track_info(track, verbose);
-    }
+#[derive(Serialize, Clone, Debug)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub error: String,
 }
 
-pub fn print_track_info(track: &Track, verbose: bool) {
-    print!("  Track: ");
+#[derive(Serialize, Clone, Debug)]
+pub struct FileCollectionInfo {
+    pub files: Vec<FileInfo>,
+    pub skipped_files: Vec<SkippedFile>,
+    pub total: Summary,
+}
 
-    if let Some(name) = &track.name {
-        println!("'{name}'");
+/// Reads the info of each file. Files that cannot be read or parsed are skipped
+/// and reported in `skipped_files` instead of aborting.
+pub fn read_gpx_files_info(paths: &[PathBuf], config: &StatsConfig) -> FileCollectionInfo {
+    let mut files = Vec::new();
+    let mut skipped_files = Vec::new();
+
+    for path in paths {
+        match read_gpx_file_info(path, config) {
+            Ok(info) => files.push(info),
+            Err(error) => skipped_files.push(SkippedFile {
+                path: path.clone(),
+                error: format!("{error:#}"),
+            }),
+        }
     }
 
-    if verbose && let Some(description) = &track.description {
-        println!("  Description:");
-        print_intended(description, "    ")
+    let total = summarize_files(&files);
+
+    FileCollectionInfo {
+        files,
+        skipped_files,
+        total,
     }
+}
 
-    println!("    Segments: {}", track.segments.len());
-    println!("    Points: {}", count_points_in_track(track));
+pub fn read_gpx_file_info(path: &Path, config: &StatsConfig) -> anyhow::Result<FileInfo> {
+    let input_file_contents =
+        fs::read(path).with_context(|| error_messages::INPUT_FILE_READ_ERROR)?;
 
-    let distance_in_m = compute_distance_of_track_in_metres(track);
-    let distance_in_km = distance_in_m / 1000.0;
+    let gpx = gpx::read(input_file_contents.as_slice())
+        .with_context(|| error_messages::GPX_PARSE_ERROR)?;
 
-    println!("    Distance: {:.2} km", distance_in_km);
-    println!();
+    compute_gpx_info(path, input_file_contents.len(), &gpx, config)
 }
 
-pub fn print_all_waypoints_info(waypoints: &Vec<Waypoint>, verbose: bool) {
-    if waypoints.is_empty() {
-        return;
-    }
-
-    println!("Waypoints: {}", waypoints.len());
+pub fn compute_gpx_info(
+    path: &Path,
+    size_in_bytes: usize,
+    gpx: &Gpx,
+    config: &StatsConfig,
+) -> anyhow::Result<FileInfo> {
+    let file_name = path
+        .file_name()
+        .with_context(|| error_messages::INPUT_PATH_MISSING_FILE_NAME)?
+        .to_string_lossy()
+        .to_string();
+
+    Ok(FileInfo {
+        path: path.to_path_buf(),
+        file_name,
+        size_in_bytes,
+        waypoints: gpx.waypoints.iter().map(compute_waypoint_info).collect(),
+        tracks: gpx
+            .tracks
+            .iter()
+            .map(|track| compute_track_info(track, config))
+            .collect(),
+        routes: gpx
+            .routes
+            .iter()
+            .map(|route| compute_route_info(route, config))
+            .collect(),
+    })
+}
 
-    for waypoint in waypoints {
-        match &waypoint.name {
-            Some(name) => println!("- '{name}'"),
-            None => println!("-  no name"),
+pub fn summarize_file(info: &FileInfo) -> Summary {
+    let mut summary = Summary {
+        file_count: 1,
+        track_count: info.tracks.len(),
+        route_count: info.routes.len(),
+        waypoint_count: info.waypoints.len(),
+        ..Default::default()
+    };
+
+    let polyline_stats = info
+        .tracks
+        .iter()
+        .map(|track| (track.distance_in_m, &track.elevation, &track.time))
+        .chain(
+            info.routes
+                .iter()
+                .map(|route| (route.distance_in_m, &route.elevation, &route.time)),
+        );
+
+    for (distance_in_m, elevation, time) in polyline_stats {
+        summary.distance_in_m += distance_in_m;
+
+        if let Some(elevation) = elevation {
+            summary.ascent_in_m += elevation.ascent_in_m;
+            summary.descent_in_m += elevation.descent_in_m;
         }
 
-        if verbose && let Some(description) = &waypoint.description {
-            println!("   Description:");
-            print_intended(description, "     ");
+        if let Some(time) = time {
+            summary.duration_in_s += time.duration_in_s;
+            summary.moving_time_in_s += time.moving_time_in_s;
         }
     }
 
-    println!();
+    summary
 }
 
-pub fn print_all_routes_info(routes: &Vec<Route>, verbose: bool) {
-    if routes.is_empty() {
-        return;
-    }
+pub fn summarize_files(infos: &[FileInfo]) -> Summary {
+    let mut total = Summary::default();
 
-    println!("Routes: {}", routes.len());
-    for route in routes {
-        print_route_info(route, verbose);
+    for summary in infos.iter().map(summarize_file) {
+        total.add(&summary);
     }
+
+    total
 }
 
-pub fn print_route_info(route: &Route, verbose: bool) {
-    print!("  Route: ");
+pub fn compute_waypoint_info(waypoint: &Waypoint) -> WaypointInfo {
+    let point = waypoint.point();
 
-    if let Some(name) = &route.name {
-        println!("'{name}'");
+    WaypointInfo {
+        name: waypoint.name.clone(),
+        description: waypoint.description.clone(),
+        latitude: point.y(),
+        longitude: point.x(),
+        elevation_in_m: waypoint.elevation,
     }
+}
 
-    if verbose && let Some(description) = &route.description {
-        println!("  Description:");
-        print_intended(description, "    ")
+pub fn compute_track_info(track: &Track, config: &StatsConfig) -> TrackInfo {
+    let polylines = get_polylines_of_track(track);
+    let profile = stats::compute_profile(&polylines);
+
+    TrackInfo {
+        name: track.name.clone(),
+        description: track.description.clone(),
+        segment_count: track.segments.len(),
+        point_count: count_points_in_track(track),
+        distance_in_m: compute_distance_of_track_in_metres(track),
+        elevation: stats::compute_elevation_stats(&polylines, config),
+        time: stats::compute_time_stats(&polylines, config),
+        splits: config
+            .splits
+            .as_ref()
+            .map(|split_config| splits::compute_splits(&profile, split_config)),
+        climbs: config
+            .climbs
+            .as_ref()
+            .map(|climb_config| climbs::detect_climbs(&profile, climb_config)),
+        profile: config.include_profile.then_some(profile),
     }
-
-    println!("    Points: {}", route.points.len());
-
-    let distance_in_m = compute_distance_of_polyline_in_metres(&route.points);
-    let distance_in_km = distance_in_m / 1000.0;
-
-    println!("    Distance: {:.2} km", distance_in_km);
-    println!();
 }
 
-fn print_intended(text: &str, prefix: &str) {
-    for line in text.lines() {
-        print!("{prefix}");
-        println!("{line}");
+pub fn compute_route_info(route: &Route, config: &StatsConfig) -> RouteInfo {
+    let polylines = [route.points.as_slice()];
+    let profile = stats::compute_profile(&polylines);
+
+    RouteInfo {
+        name: route.name.clone(),
+        description: route.description.clone(),
+        point_count: route.points.len(),
+        distance_in_m: compute_distance_of_polyline_in_metres(&route.points),
+        elevation: stats::compute_elevation_stats(&polylines, config),
+        time: stats::compute_time_stats(&polylines, config),
+        climbs: config
+            .climbs
+            .as_ref()
+            .map(|climb_config| climbs::detect_climbs(&profile, climb_config)),
+        profile: config.include_profile.then_some(profile),
     }
 }
 
+pub fn get_polylines_of_track(track: &Track) -> Vec<&[Waypoint]> {
+    track
+        .segments
+        .iter()
+        .map(|segment| segment.points.as_slice())
+        .collect()
+}
+
 fn count_points_in_track(track: &Track) -> usize {
     track
         .segments
@@ -141,7 +283,7 @@ fn count_points_in_track(track: &Track) -> usize {
         .sum()
 }
 
-fn compute_distance_of_polyline_in_metres(points: &Vec<Waypoint>) -> f64 {
+pub fn compute_distance_of_polyline_in_metres(points: &[Waypoint]) -> f64 {
     let mut distance: f64 = 0.0;
 
     for i in 1..points.len() {
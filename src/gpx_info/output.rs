@@ -0,0 +1,727 @@
+use super::chart;
+use super::chart::ChartConfig;
+use super::climbs::Climb;
+use super::info;
+use super::info::{FileCollectionInfo, FileInfo, RouteInfo, Summary, TrackInfo, WaypointInfo};
+use super::splits::{DistanceUnit, Split};
+use super::stats::{ElevationStats, ProfilePoint, TimeStats};
+use crate::error_messages;
+use anyhow::Context;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io;
+use std::io::Write;
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON document containing all information.
+    Json,
+    /// CSV table with one row per track and route, followed by a total row.
+    /// If splits, climbs or profiles are requested, one row per split / climb / point instead.
+    Csv,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    pub verbose: bool,
+    /// Unit to display the splits in. `None` if no splits were computed.
+    pub split_unit: Option<DistanceUnit>,
+    pub show_climbs: bool,
+    /// Configuration of the elevation profile charts. `None` if no charts should be drawn.
+    pub chart: Option<ChartConfig>,
+}
+
+impl OutputOptions {
+    fn has_details(&self) -> bool {
+        self.split_unit.is_some() || self.show_climbs || self.chart.is_some()
+    }
+}
+
+pub fn print_collection_info(
+    info: &FileCollectionInfo,
+    options: &OutputOptions,
+) -> anyhow::Result<()> {
+    match options.format {
+        OutputFormat::Text => {
+            print_collection_info_as_text(info, options);
+            Ok(())
+        }
+        OutputFormat::Json => print_collection_info_as_json(info),
+        OutputFormat::Csv if options.split_unit.is_some() => print_splits_as_csv(info),
+        OutputFormat::Csv if options.show_climbs => print_climbs_as_csv(info),
+        OutputFormat::Csv if options.chart.is_some() => print_profiles_as_csv(info),
+        OutputFormat::Csv => print_collection_info_as_csv(info),
+    }
+}
+
+pub fn print_collection_info_as_text(info: &FileCollectionInfo, options: &OutputOptions) {
+    let is_single_file = info.files.len() == 1 && info.skipped_files.is_empty();
+
+    // Multiple files are only summarised in a table, unless details such as the splits
+    // were explicitly requested.
+    if is_single_file || options.has_details() {
+        for file in &info.files {
+            print_file_info_as_text(file, options);
+        }
+    }
+
+    if !is_single_file {
+        print_summary_table(&info.files, &info.total);
+    }
+}
+
+pub fn print_collection_info_as_json(info: &FileCollectionInfo) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    serde_json::to_writer_pretty(&mut stdout, info)
+        .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+    writeln!(stdout).with_context(|| error_messages::STDOUT_WRITE_ERROR)?;
+
+    Ok(())
+}
+
+/// A single row of the CSV output. Describes either a track or a route.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    file: &'a str,
+    #[serde(rename = "type")]
+    type_: &'a str,
+    name: Option<&'a str>,
+    segments: Option<usize>,
+    points: Option<usize>,
+    distance_km: f64,
+    ascent_m: Option<f64>,
+    descent_m: Option<f64>,
+    min_elevation_m: Option<f64>,
+    max_elevation_m: Option<f64>,
+    duration_s: Option<f64>,
+    moving_time_s: Option<f64>,
+    stopped_time_s: Option<f64>,
+    avg_moving_speed_kmh: Option<f64>,
+    max_speed_kmh: Option<f64>,
+}
+
+impl<'a> CsvRow<'a> {
+    fn new(
+        file: &'a str,
+        type_: &'a str,
+        name: &'a Option<String>,
+        points: usize,
+        distance_in_m: f64,
+        elevation: &Option<ElevationStats>,
+        time: &Option<TimeStats>,
+    ) -> Self {
+        CsvRow {
+            file,
+            type_,
+            name: name.as_deref(),
+            segments: None,
+            points: Some(points),
+            distance_km: distance_in_m / 1000.0,
+            ascent_m: elevation.as_ref().map(|e| e.ascent_in_m),
+            descent_m: elevation.as_ref().map(|e| e.descent_in_m),
+            min_elevation_m: elevation.as_ref().map(|e| e.min_elevation_in_m),
+            max_elevation_m: elevation.as_ref().map(|e| e.max_elevation_in_m),
+            duration_s: time.as_ref().map(|t| t.duration_in_s),
+            moving_time_s: time.as_ref().map(|t| t.moving_time_in_s),
+            stopped_time_s: time.as_ref().map(|t| t.stopped_time_in_s),
+            avg_moving_speed_kmh: time
+                .as_ref()
+                .map(|t| mps_to_kmh(t.average_moving_speed_in_mps)),
+            max_speed_kmh: time.as_ref().map(|t| mps_to_kmh(t.max_speed_in_mps)),
+        }
+    }
+
+    fn from_track(file: &'a str, track: &'a TrackInfo) -> Self {
+        CsvRow {
+            segments: Some(track.segment_count),
+            ..CsvRow::new(
+                file,
+                "track",
+                &track.name,
+                track.point_count,
+                track.distance_in_m,
+                &track.elevation,
+                &track.time,
+            )
+        }
+    }
+
+    fn from_route(file: &'a str, route: &'a RouteInfo) -> Self {
+        CsvRow::new(
+            file,
+            "route",
+            &route.name,
+            route.point_count,
+            route.distance_in_m,
+            &route.elevation,
+            &route.time,
+        )
+    }
+
+    fn from_total(total: &Summary) -> Self {
+        CsvRow {
+            file: "",
+            type_: "total",
+            name: None,
+            segments: None,
+            points: None,
+            distance_km: total.distance_in_m / 1000.0,
+            ascent_m: Some(total.ascent_in_m),
+            descent_m: Some(total.descent_in_m),
+            min_elevation_m: None,
+            max_elevation_m: None,
+            duration_s: Some(total.duration_in_s),
+            moving_time_s: Some(total.moving_time_in_s),
+            stopped_time_s: None,
+            avg_moving_speed_kmh: None,
+            max_speed_kmh: None,
+        }
+    }
+}
+
+pub fn print_collection_info_as_csv(info: &FileCollectionInfo) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout().lock());
+
+    for file in &info.files {
+        let file_path = file.path.to_string_lossy();
+
+        for track in &file.tracks {
+            writer
+                .serialize(CsvRow::from_track(&file_path, track))
+                .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+        }
+
+        for route in &file.routes {
+            writer
+                .serialize(CsvRow::from_route(&file_path, route))
+                .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+        }
+    }
+
+    writer
+        .serialize(CsvRow::from_total(&info.total))
+        .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+
+    writer
+        .flush()
+        .with_context(|| error_messages::STDOUT_WRITE_ERROR)?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct SplitCsvRow<'a> {
+    file: &'a str,
+    track: Option<&'a str>,
+    split: usize,
+    distance_km: f64,
+    total_distance_km: f64,
+    time_s: Option<f64>,
+    total_time_s: Option<f64>,
+    pace_s_per_km: Option<f64>,
+    pace_s_per_mi: Option<f64>,
+    elevation_change_m: Option<f64>,
+}
+
+pub fn print_splits_as_csv(info: &FileCollectionInfo) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout().lock());
+
+    for file in &info.files {
+        let file_path = file.path.to_string_lossy();
+
+        for track in &file.tracks {
+            for split in track.splits.iter().flatten() {
+                let row = SplitCsvRow {
+                    file: &file_path,
+                    track: track.name.as_deref(),
+                    split: split.number,
+                    distance_km: split.distance_in_m / 1000.0,
+                    total_distance_km: split.total_distance_in_m / 1000.0,
+                    time_s: split.elapsed_time_in_s,
+                    total_time_s: split.total_time_in_s,
+                    pace_s_per_km: split.pace_in_s_per_km,
+                    pace_s_per_mi: split.pace_in_s_per_mi,
+                    elevation_change_m: split.elevation_change_in_m,
+                };
+
+                writer
+                    .serialize(row)
+                    .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+            }
+        }
+    }
+
+    writer
+        .flush()
+        .with_context(|| error_messages::STDOUT_WRITE_ERROR)?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ClimbCsvRow<'a> {
+    file: &'a str,
+    #[serde(rename = "type")]
+    type_: &'a str,
+    name: Option<&'a str>,
+    start_km: f64,
+    end_km: f64,
+    length_km: f64,
+    gain_m: f64,
+    average_gradient_percent: f64,
+    max_gradient_percent: f64,
+    category: Option<&'a str>,
+}
+
+impl<'a> ClimbCsvRow<'a> {
+    fn new(file: &'a str, type_: &'a str, name: &'a Option<String>, climb: &Climb) -> Self {
+        ClimbCsvRow {
+            file,
+            type_,
+            name: name.as_deref(),
+            start_km: climb.start_distance_in_m / 1000.0,
+            end_km: climb.end_distance_in_m / 1000.0,
+            length_km: climb.length_in_m / 1000.0,
+            gain_m: climb.gain_in_m,
+            average_gradient_percent: climb.average_gradient_percent,
+            max_gradient_percent: climb.max_gradient_percent,
+            category: climb.category.map(|category| category.label()),
+        }
+    }
+}
+
+pub fn print_climbs_as_csv(info: &FileCollectionInfo) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout().lock());
+
+    for file in &info.files {
+        let file_path = file.path.to_string_lossy();
+
+        let track_climbs = file.tracks.iter().flat_map(|track| {
+            track
+                .climbs
+                .iter()
+                .flatten()
+                .map(|climb| ClimbCsvRow::new(&file_path, "track", &track.name, climb))
+        });
+        let route_climbs = file.routes.iter().flat_map(|route| {
+            route
+                .climbs
+                .iter()
+                .flatten()
+                .map(|climb| ClimbCsvRow::new(&file_path, "route", &route.name, climb))
+        });
+
+        for row in track_climbs.chain(route_climbs) {
+            writer
+                .serialize(row)
+                .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+        }
+    }
+
+    writer
+        .flush()
+        .with_context(|| error_messages::STDOUT_WRITE_ERROR)?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ProfileCsvRow<'a> {
+    file: &'a str,
+    #[serde(rename = "type")]
+    type_: &'a str,
+    name: Option<&'a str>,
+    distance_km: f64,
+    elevation_m: Option<f64>,
+}
+
+impl<'a> ProfileCsvRow<'a> {
+    fn new(file: &'a str, type_: &'a str, name: &'a Option<String>, point: &ProfilePoint) -> Self {
+        ProfileCsvRow {
+            file,
+            type_,
+            name: name.as_deref(),
+            distance_km: point.distance_in_m / 1000.0,
+            elevation_m: point.elevation_in_m,
+        }
+    }
+}
+
+pub fn print_profiles_as_csv(info: &FileCollectionInfo) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout().lock());
+
+    for file in &info.files {
+        let file_path = file.path.to_string_lossy();
+
+        let track_points = file.tracks.iter().flat_map(|track| {
+            track
+                .profile
+                .iter()
+                .flatten()
+                .map(|point| ProfileCsvRow::new(&file_path, "track", &track.name, point))
+        });
+        let route_points = file.routes.iter().flat_map(|route| {
+            route
+                .profile
+                .iter()
+                .flatten()
+                .map(|point| ProfileCsvRow::new(&file_path, "route", &route.name, point))
+        });
+
+        for row in track_points.chain(route_points) {
+            writer
+                .serialize(row)
+                .with_context(|| error_messages::OUTPUT_SERIALIZE_ERROR)?;
+        }
+    }
+
+    writer
+        .flush()
+        .with_context(|| error_messages::STDOUT_WRITE_ERROR)?;
+
+    Ok(())
+}
+
+pub fn print_file_info_as_text(info: &FileInfo, options: &OutputOptions) {
+    let verbose = options.verbose;
+
+    println!("File: {}", info.file_name);
+
+    if verbose {
+        let size_in_kb = info.size_in_bytes as f64 / 1000.0;
+        println!("Size: {:.1} KB", size_in_kb);
+    }
+
+    println!();
+
+    print_all_waypoints_info(&info.waypoints, verbose);
+
+    print_all_tracks_info(&info.tracks, options);
+
+    print_all_routes_info(&info.routes, options);
+}
+
+fn print_summary_table(files: &[FileInfo], total: &Summary) {
+    let rows: Vec<(String, Summary)> = files
+        .iter()
+        .map(|file| (file.path.display().to_string(), info::summarize_file(file)))
+        .collect();
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+
+    println!(
+        "{:<name_width$}  {:>6}  {:>6}  {:>9}  {:>13}  {:>10}  {:>11}  {:>10}",
+        "File",
+        "Tracks",
+        "Routes",
+        "Waypoints",
+        "Distance (km)",
+        "Ascent (m)",
+        "Descent (m)",
+        "Duration"
+    );
+
+    for (name, summary) in &rows {
+        print_summary_row(name, summary, name_width);
+    }
+
+    println!();
+    print_summary_row("Total", total, name_width);
+}
+
+fn print_summary_row(name: &str, summary: &Summary, name_width: usize) {
+    println!(
+        "{:<name_width$}  {:>6}  {:>6}  {:>9}  {:>13.2}  {:>10.0}  {:>11.0}  {:>10}",
+        name,
+        summary.track_count,
+        summary.route_count,
+        summary.waypoint_count,
+        summary.distance_in_m / 1000.0,
+        summary.ascent_in_m,
+        summary.descent_in_m,
+        format_duration(summary.duration_in_s),
+    );
+}
+
+fn print_all_tracks_info(tracks: &[TrackInfo], options: &OutputOptions) {
+    if tracks.is_empty() {
+        return;
+    }
+
+    println!("Tracks: {}", tracks.len());
+
+    for track in tracks {
+        print_track_info(track, options);
+    }
+}
+
+fn print_track_info(track: &TrackInfo, options: &OutputOptions) {
+    let verbose = options.verbose;
+
+    print!("  Track: ");
+
+    if let Some(name) = &track.name {
+        println!("'{name}'");
+    }
+
+    if verbose && let Some(description) = &track.description {
+        println!("  Description:");
+        print_intended(description, "    ")
+    }
+
+    println!("    Segments: {}", track.segment_count);
+    println!("    Points: {}", track.point_count);
+
+    let distance_in_km = track.distance_in_m / 1000.0;
+
+    println!("    Distance: {:.2} km", distance_in_km);
+
+    print_polyline_stats(&track.elevation, &track.time);
+
+    if let (Some(splits), Some(unit)) = (&track.splits, options.split_unit) {
+        print_splits(splits, unit);
+    }
+
+    if let Some(climbs) = &track.climbs {
+        print_climbs(climbs);
+    }
+
+    if let (Some(profile), Some(chart_config)) = (&track.profile, &options.chart) {
+        print_elevation_chart(profile, chart_config);
+    }
+
+    println!();
+}
+
+fn print_elevation_chart(profile: &[ProfilePoint], config: &ChartConfig) {
+    let indent = "    ";
+
+    let chart_config = ChartConfig {
+        width: config.width.saturating_sub(indent.len() + 1),
+        ..config.clone()
+    };
+
+    match chart::render_elevation_chart(profile, &chart_config) {
+        Some(lines) => {
+            println!("{indent}Elevation profile:");
+            for line in lines {
+                println!("{indent}{line}");
+            }
+        }
+        None => println!("{indent}Elevation profile: no elevation data"),
+    }
+}
+
+fn print_climbs(climbs: &[Climb]) {
+    if climbs.is_empty() {
+        println!("    Climbs: none");
+        return;
+    }
+
+    println!("    Climbs:");
+    println!(
+        "      {:>4}  {:>17}  {:>8}  {:>6}  {:>6}  {:>6}  {:>4}",
+        "#", "Start - End", "Length", "Gain", "Avg.", "Max.", "Cat."
+    );
+
+    for (i, climb) in climbs.iter().enumerate() {
+        let range = format!(
+            "{:.2} - {:.2} km",
+            climb.start_distance_in_m / 1000.0,
+            climb.end_distance_in_m / 1000.0
+        );
+
+        println!(
+            "      {:>4}  {:>17}  {:>5.2} km  {:>4.0} m  {:>5.1}%  {:>5.1}%  {:>4}",
+            i + 1,
+            range,
+            climb.length_in_m / 1000.0,
+            climb.gain_in_m,
+            climb.average_gradient_percent,
+            climb.max_gradient_percent,
+            climb.category.map_or("-", |category| category.label())
+        );
+    }
+}
+
+fn print_splits(splits: &[Split], unit: DistanceUnit) {
+    let symbol = unit.symbol();
+
+    println!("    Splits:");
+    println!(
+        "      {:>4}  {:>10}  {:>10}  {:>10}  {:>10}  {:>8}",
+        "#", "Distance", "Total", "Time", "Pace", "Elev."
+    );
+
+    for split in splits {
+        let distance = format!("{:.2} {symbol}", split.distance_in_m / unit.in_metres());
+        let total_distance = format!(
+            "{:.2} {symbol}",
+            split.total_distance_in_m / unit.in_metres()
+        );
+
+        let time = split
+            .elapsed_time_in_s
+            .map_or_else(|| "-".to_string(), format_duration);
+
+        let pace_in_s = match unit {
+            DistanceUnit::Kilometres => split.pace_in_s_per_km,
+            DistanceUnit::Miles => split.pace_in_s_per_mi,
+        };
+        let pace = pace_in_s.map_or_else(
+            || "-".to_string(),
+            |pace| format!("{}/{symbol}", format_pace(pace)),
+        );
+
+        let elevation_change = split
+            .elevation_change_in_m
+            .map_or_else(|| "-".to_string(), |change| format!("{change:+.0} m"));
+
+        println!(
+            "      {:>4}  {:>10}  {:>10}  {:>10}  {:>10}  {:>8}",
+            split.number, distance, total_distance, time, pace, elevation_change
+        );
+    }
+}
+
+fn print_all_waypoints_info(waypoints: &[WaypointInfo], verbose: bool) {
+    if waypoints.is_empty() {
+        return;
+    }
+
+    println!("Waypoints: {}", waypoints.len());
+
+    for waypoint in waypoints {
+        match &waypoint.name {
+            Some(name) => println!("- '{name}'"),
+            None => println!("-  no name"),
+        }
+
+        if verbose && let Some(description) = &waypoint.description {
+            println!("   Description:");
+            print_intended(description, "     ");
+        }
+    }
+
+    println!();
+}
+
+fn print_all_routes_info(routes: &[RouteInfo], options: &OutputOptions) {
+    if routes.is_empty() {
+        return;
+    }
+
+    println!("Routes: {}", routes.len());
+    for route in routes {
+        print_route_info(route, options);
+    }
+}
+
+fn print_route_info(route: &RouteInfo, options: &OutputOptions) {
+    let verbose = options.verbose;
+
+    print!("  Route: ");
+
+    if let Some(name) = &route.name {
+        println!("'{name}'");
+    }
+
+    if verbose && let Some(description) = &route.description {
+        println!("  Description:");
+        print_intended(description, "    ")
+    }
+
+    println!("    Points: {}", route.point_count);
+
+    let distance_in_km = route.distance_in_m / 1000.0;
+
+    println!("    Distance: {:.2} km", distance_in_km);
+
+    print_polyline_stats(&route.elevation, &route.time);
+
+    if let Some(climbs) = &route.climbs {
+        print_climbs(climbs);
+    }
+
+    if let (Some(profile), Some(chart_config)) = (&route.profile, &options.chart) {
+        print_elevation_chart(profile, chart_config);
+    }
+
+    println!();
+}
+
+fn print_polyline_stats(elevation: &Option<ElevationStats>, time: &Option<TimeStats>) {
+    if let Some(elevation_stats) = elevation {
+        print_elevation_stats(elevation_stats);
+    }
+
+    if let Some(time_stats) = time {
+        print_time_stats(time_stats);
+    }
+}
+
+fn print_elevation_stats(stats: &ElevationStats) {
+    println!("    Ascent: {:.0} m", stats.ascent_in_m);
+    println!("    Descent: {:.0} m", stats.descent_in_m);
+    println!(
+        "    Elevation: {:.0} m - {:.0} m",
+        stats.min_elevation_in_m, stats.max_elevation_in_m
+    );
+}
+
+fn print_time_stats(stats: &TimeStats) {
+    println!("    Duration: {}", format_duration(stats.duration_in_s));
+    println!(
+        "    Moving time: {}",
+        format_duration(stats.moving_time_in_s)
+    );
+    println!(
+        "    Stopped time: {}",
+        format_duration(stats.stopped_time_in_s)
+    );
+    println!(
+        "    Avg. moving speed: {:.1} km/h",
+        mps_to_kmh(stats.average_moving_speed_in_mps)
+    );
+    println!(
+        "    Max. speed: {:.1} km/h",
+        mps_to_kmh(stats.max_speed_in_mps)
+    );
+}
+
+/// Formats a duration as "h:mm:ss".
+pub fn format_duration(duration_in_s: f64) -> String {
+    let total_seconds = duration_in_s.round() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    format!("{hours}:{minutes:02}:{seconds:02}")
+}
+
+/// Formats a pace as "m:ss".
+fn format_pace(pace_in_s: f64) -> String {
+    let total_seconds = pace_in_s.round() as u64;
+    let minutes = total_seconds / 60;
+    let seconds = total_seconds % 60;
+    format!("{minutes}:{seconds:02}")
+}
+
+fn mps_to_kmh(speed_in_mps: f64) -> f64 {
+    speed_in_mps * 3.6
+}
+
+fn print_intended(text: &str, prefix: &str) {
+    for line in text.lines() {
+        print!("{prefix}");
+        println!("{line}");
+    }
+}
@@ -0,0 +1,176 @@
+use super::stats::ProfilePoint;
+use serde::Serialize;
+
+pub const METRES_PER_KILOMETRE: f64 = 1000.0;
+pub const METRES_PER_MILE: f64 = 1609.344;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DistanceUnit {
+    Kilometres,
+    Miles,
+}
+
+impl DistanceUnit {
+    pub fn in_metres(self) -> f64 {
+        match self {
+            DistanceUnit::Kilometres => METRES_PER_KILOMETRE,
+            DistanceUnit::Miles => METRES_PER_MILE,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            DistanceUnit::Kilometres => "km",
+            DistanceUnit::Miles => "mi",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SplitConfig {
+    pub interval_in_m: f64,
+    /// Unit used to display distances and paces.
+    pub unit: DistanceUnit,
+}
+
+/// Parses a split interval such as "1km", "1mi", "0.5km" or "400m".
+/// A number without unit is interpreted as kilometres.
+pub fn parse_split_config(text: &str) -> Result<SplitConfig, String> {
+    let text = text.trim().to_lowercase();
+
+    let (number, unit, unit_in_metres) = if let Some(number) = text.strip_suffix("km") {
+        (number, DistanceUnit::Kilometres, METRES_PER_KILOMETRE)
+    } else if let Some(number) = text.strip_suffix("mi") {
+        (number, DistanceUnit::Miles, METRES_PER_MILE)
+    } else if let Some(number) = text.strip_suffix('m') {
+        (number, DistanceUnit::Kilometres, 1.0)
+    } else {
+        (
+            text.as_str(),
+            DistanceUnit::Kilometres,
+            METRES_PER_KILOMETRE,
+        )
+    };
+
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid split interval '{text}' (expected e.g. 1km, 1mi, 500m)"))?;
+
+    if number <= 0.0 || !number.is_finite() {
+        return Err("split interval must be positive".to_string());
+    }
+
+    Ok(SplitConfig {
+        interval_in_m: number * unit_in_metres,
+        unit,
+    })
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Split {
+    pub number: usize,
+    /// Length of this split. Only the last split may be shorter than the interval.
+    pub distance_in_m: f64,
+    /// Distance from the start to the end of this split.
+    pub total_distance_in_m: f64,
+    pub elapsed_time_in_s: Option<f64>,
+    /// Time from the start to the end of this split.
+    pub total_time_in_s: Option<f64>,
+    pub pace_in_s_per_km: Option<f64>,
+    pub pace_in_s_per_mi: Option<f64>,
+    /// Net elevation difference between the end and the start of this split.
+    pub elevation_change_in_m: Option<f64>,
+}
+
+/// Divides the profile into splits of equal distance. The values at the split boundaries
+/// are linearly interpolated between the two neighbouring points.
+pub fn compute_splits(profile: &[ProfilePoint], config: &SplitConfig) -> Vec<Split> {
+    let mut splits = Vec::new();
+
+    let (Some(first), Some(last)) = (profile.first(), profile.last()) else {
+        return splits;
+    };
+
+    let total_distance_in_m = last.distance_in_m;
+    let mut split_start = first.clone();
+    let mut next_boundary_in_m = config.interval_in_m;
+    let mut i = 1;
+
+    loop {
+        let boundary_in_m = next_boundary_in_m.min(total_distance_in_m);
+
+        while i < profile.len() - 1 && profile[i].distance_in_m < boundary_in_m {
+            i += 1;
+        }
+
+        if i >= profile.len() {
+            break;
+        }
+
+        let split_end = interpolate_at_distance(&profile[i - 1], &profile[i], boundary_in_m);
+
+        if split_end.distance_in_m > split_start.distance_in_m {
+            splits.push(create_split(
+                splits.len() + 1,
+                first,
+                &split_start,
+                &split_end,
+            ));
+        }
+
+        if boundary_in_m >= total_distance_in_m {
+            break;
+        }
+
+        split_start = split_end;
+        next_boundary_in_m += config.interval_in_m;
+    }
+
+    splits
+}
+
+fn create_split(
+    number: usize,
+    first: &ProfilePoint,
+    start: &ProfilePoint,
+    end: &ProfilePoint,
+) -> Split {
+    let distance_in_m = end.distance_in_m - start.distance_in_m;
+    let elapsed_time_in_s = subtract_optional(end.time_in_s, start.time_in_s);
+    let pace_in_s_per_m = elapsed_time_in_s.map(|time| time / distance_in_m);
+
+    Split {
+        number,
+        distance_in_m,
+        total_distance_in_m: end.distance_in_m,
+        elapsed_time_in_s,
+        total_time_in_s: subtract_optional(end.time_in_s, first.time_in_s),
+        pace_in_s_per_km: pace_in_s_per_m.map(|pace| pace * METRES_PER_KILOMETRE),
+        pace_in_s_per_mi: pace_in_s_per_m.map(|pace| pace * METRES_PER_MILE),
+        elevation_change_in_m: subtract_optional(end.elevation_in_m, start.elevation_in_m),
+    }
+}
+
+fn interpolate_at_distance(a: &ProfilePoint, b: &ProfilePoint, distance_in_m: f64) -> ProfilePoint {
+    let length = b.distance_in_m - a.distance_in_m;
+    let t = if length > 0.0 {
+        ((distance_in_m - a.distance_in_m) / length).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    ProfilePoint {
+        distance_in_m,
+        elevation_in_m: interpolate_optional(a.elevation_in_m, b.elevation_in_m, t),
+        time_in_s: interpolate_optional(a.time_in_s, b.time_in_s, t),
+    }
+}
+
+fn interpolate_optional(a: Option<f64>, b: Option<f64>, t: f64) -> Option<f64> {
+    Some(a? + (b? - a?) * t)
+}
+
+fn subtract_optional(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    Some(a? - b?)
+}
@@ -0,0 +1,183 @@
+use super::climbs::ClimbConfig;
+use super::info::compute_distance_of_polyline_in_metres;
+use super::splits::SplitConfig;
+use geo::{Distance, Haversine};
+use gpx::Waypoint;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+pub const DEFAULT_ELEVATION_THRESHOLD: f64 = 5.0;
+pub const DEFAULT_MOVING_SPEED_THRESHOLD: f64 = 0.5;
+
+#[derive(Clone, Debug)]
+pub struct StatsConfig {
+    /// Minimum elevation change (in metres) before it is counted as ascent / descent.
+    /// Smaller changes are treated as noise.
+    pub elevation_threshold: f64,
+    /// Minimum speed (in m/s) between two points to count the time in between as moving.
+    pub moving_speed_threshold: f64,
+    /// Compute a split table for each track.
+    pub splits: Option<SplitConfig>,
+    /// Detect climbs in each track and route.
+    pub climbs: Option<ClimbConfig>,
+    /// Keep the elevation profile of each track and route.
+    pub include_profile: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ElevationStats {
+    pub ascent_in_m: f64,
+    pub descent_in_m: f64,
+    pub min_elevation_in_m: f64,
+    pub max_elevation_in_m: f64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TimeStats {
+    /// Time between the first and the last timestamp.
+    pub duration_in_s: f64,
+    pub moving_time_in_s: f64,
+    pub stopped_time_in_s: f64,
+    pub average_moving_speed_in_mps: f64,
+    pub max_speed_in_mps: f64,
+}
+
+/// A point along a polyline, described by its distance from the start.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ProfilePoint {
+    pub distance_in_m: f64,
+    pub elevation_in_m: Option<f64>,
+    /// Unix timestamp in seconds.
+    pub time_in_s: Option<f64>,
+}
+
+/// Computes the cumulative distance of each point along multiple polylines (e.g. the segments
+/// of a track). The gaps between polylines do not count towards the distance.
+pub fn compute_profile(polylines: &[&[Waypoint]]) -> Vec<ProfilePoint> {
+    let mut profile = Vec::new();
+    let mut distance_in_m: f64 = 0.0;
+
+    for points in polylines {
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                distance_in_m += compute_distance_of_polyline_in_metres(&points[i - 1..=i]);
+            }
+
+            profile.push(ProfilePoint {
+                distance_in_m,
+                elevation_in_m: point.elevation,
+                time_in_s: point
+                    .time
+                    .map(|time| OffsetDateTime::from(time).unix_timestamp_nanos() as f64 / 1e9),
+            });
+        }
+    }
+
+    profile
+}
+
+/// Computes the elevation statistics over multiple polylines (e.g. the segments of a track).
+/// Returns `None` if no point has elevation data.
+pub fn compute_elevation_stats(
+    polylines: &[&[Waypoint]],
+    config: &StatsConfig,
+) -> Option<ElevationStats> {
+    let mut stats: Option<ElevationStats> = None;
+
+    for points in polylines {
+        // The reference elevation is only moved once the change exceeds the threshold,
+        // so that small oscillations do not add up.
+        let mut reference_elevation: Option<f64> = None;
+
+        for elevation in points.iter().filter_map(|point| point.elevation) {
+            let stats = stats.get_or_insert(ElevationStats {
+                ascent_in_m: 0.0,
+                descent_in_m: 0.0,
+                min_elevation_in_m: elevation,
+                max_elevation_in_m: elevation,
+            });
+
+            stats.min_elevation_in_m = stats.min_elevation_in_m.min(elevation);
+            stats.max_elevation_in_m = stats.max_elevation_in_m.max(elevation);
+
+            let Some(reference) = reference_elevation else {
+                reference_elevation = Some(elevation);
+                continue;
+            };
+
+            let change = elevation - reference;
+
+            if change >= config.elevation_threshold {
+                stats.ascent_in_m += change;
+                reference_elevation = Some(elevation);
+            } else if -change >= config.elevation_threshold {
+                stats.descent_in_m -= change;
+                reference_elevation = Some(elevation);
+            }
+        }
+    }
+
+    stats
+}
+
+/// Computes the time statistics over multiple polylines (e.g. the segments of a track).
+/// Returns `None` if fewer than two points have a timestamp.
+pub fn compute_time_stats(polylines: &[&[Waypoint]], config: &StatsConfig) -> Option<TimeStats> {
+    let mut first_time: Option<OffsetDateTime> = None;
+    let mut last_time: Option<OffsetDateTime> = None;
+
+    let mut moving_time_in_s: f64 = 0.0;
+    let mut moving_distance_in_m: f64 = 0.0;
+    let mut max_speed_in_mps: f64 = 0.0;
+
+    for points in polylines {
+        let mut previous: Option<(&Waypoint, OffsetDateTime)> = None;
+
+        for point in points.iter() {
+            let Some(time) = point.time.map(OffsetDateTime::from) else {
+                continue;
+            };
+
+            first_time = Some(first_time.map_or(time, |first| first.min(time)));
+            last_time = Some(last_time.map_or(time, |last| last.max(time)));
+
+            if let Some((previous_point, previous_time)) = previous {
+                let elapsed_in_s = (time - previous_time).as_seconds_f64();
+                let distance_in_m = Haversine.distance(previous_point.point(), point.point());
+
+                if elapsed_in_s > 0.0 {
+                    let speed_in_mps = distance_in_m / elapsed_in_s;
+
+                    if speed_in_mps >= config.moving_speed_threshold {
+                        moving_time_in_s += elapsed_in_s;
+                        moving_distance_in_m += distance_in_m;
+                        max_speed_in_mps = max_speed_in_mps.max(speed_in_mps);
+                    }
+                }
+            }
+
+            previous = Some((point, time));
+        }
+    }
+
+    let (first_time, last_time) = (first_time?, last_time?);
+    if first_time == last_time {
+        return None;
+    }
+
+    let duration_in_s = (last_time - first_time).as_seconds_f64();
+
+    let average_moving_speed_in_mps = if moving_time_in_s > 0.0 {
+        moving_distance_in_m / moving_time_in_s
+    } else {
+        0.0
+    };
+
+    Some(TimeStats {
+        duration_in_s,
+        moving_time_in_s,
+        stopped_time_in_s: (duration_in_s - moving_time_in_s).max(0.0),
+        average_moving_speed_in_mps,
+        max_speed_in_mps,
+    })
+}
@@ -1,4 +1,5 @@
 use super::minifier;
+use crate::gpx_clean::precision::PrecisionConfig;
 use crate::util;
 use clap::Parser;
 use std::path::PathBuf;
@@ -15,6 +16,14 @@ pub struct Args {
     /// Quiet: Disable logging.
     #[arg(short = 'q', long = "quiet")]
     quiet: bool,
+
+    /// Round latitude and longitude to this number of decimals (5 decimals are about 1 m).
+    #[arg(long = "coordinate-precision")]
+    coordinate_precision: Option<u32>,
+
+    /// Round elevations to this number of decimals.
+    #[arg(long = "elevation-precision")]
+    elevation_precision: Option<u32>,
 }
 
 pub fn run_cli() -> anyhow::Result<()> {
@@ -28,5 +37,10 @@ pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
     let input_path = args.input;
     let output_path = util::process_output_path(args.output, &input_path)?;
 
-    minifier::minify(&input_path, &output_path)
+    let precision = PrecisionConfig {
+        coordinate_decimals: args.coordinate_precision,
+        elevation_decimals: args.elevation_precision,
+    };
+
+    minifier::minify(&input_path, &output_path, &precision)
 }
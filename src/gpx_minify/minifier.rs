@@ -1,4 +1,5 @@
 use crate::error_messages;
+use crate::gpx_clean::precision::{PrecisionConfig, format_with_decimals};
 use anyhow::Context;
 use log::info;
 use std::borrow::Cow;
@@ -8,12 +9,26 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 use xml::attribute::{Attribute, OwnedAttribute};
 use xml::reader::XmlEvent;
+use xml::writer::EventWriter;
 use xml::{EmitterConfig, EventReader};
 
 use crate::error_messages::GPX_SERIALIZE_ERROR;
 use xml::writer::XmlEvent as WriterEvent;
 
-pub fn minify(input_path: &Path, output_path: &Path) -> anyhow::Result<()> {
+/// Point elements whose coordinates and elevation are rounded.
+const POINT_ELEMENTS: [&str; 3] = ["wpt", "trkpt", "rtept"];
+
+/// Point elements that are removed if they are identical to the previous point after rounding.
+const POLYLINE_POINT_ELEMENTS: [&str; 2] = ["trkpt", "rtept"];
+
+/// Latitude, longitude, and elevation of a point, as written to the output.
+type PointKey = (Option<String>, Option<String>, Option<String>);
+
+pub fn minify(
+    input_path: &Path,
+    output_path: &Path,
+    precision: &PrecisionConfig,
+) -> anyhow::Result<()> {
     info!("Writing output to {}...", output_path.display());
 
     let input_file_contents =
@@ -29,54 +44,161 @@ pub fn minify(input_path: &Path, output_path: &Path) -> anyhow::Result<()> {
         .perform_indent(false)
         .create_writer(&mut writer);
 
+    let mut element_stack: Vec<String> = Vec::new();
+    // The events of the current track / route point. They are only written once the point is
+    // complete, as it may turn out to be a duplicate of the previous point.
+    let mut point_events: Vec<XmlEvent> = Vec::new();
+    let mut point_key: PointKey = (None, None, None);
+    let mut previous_point_key: Option<PointKey> = None;
+    let mut removed_count = 0;
+
     for event in parser {
-        let event = event.with_context(|| error_messages::GPX_PARSE_ERROR)?;
-        let result = match event {
+        let mut event = event.with_context(|| error_messages::GPX_PARSE_ERROR)?;
+        let parent = element_stack.last().map(String::as_str);
+
+        match &mut event {
             XmlEvent::StartElement {
-                name,
-                attributes,
-                namespace,
+                name, attributes, ..
             } => {
-                let cloned_attributes: Vec<Attribute> =
-                    attributes.iter().map(OwnedAttribute::borrow).collect();
-                emitter.write(WriterEvent::StartElement {
-                    name: name.borrow(),
-                    attributes: Cow::Borrowed(cloned_attributes.as_slice()),
-                    namespace: Cow::Owned(namespace),
-                })
+                if POINT_ELEMENTS.contains(&name.local_name.as_str()) {
+                    round_coordinates(attributes, precision);
+                }
+
+                if POLYLINE_POINT_ELEMENTS.contains(&name.local_name.as_str()) {
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|attribute| attribute.name.local_name == key)
+                            .map(|attribute| attribute.value.clone())
+                    };
+                    point_key = (attribute("lat"), attribute("lon"), None);
+                } else if point_events.is_empty() {
+                    // A new track segment or route starts, or something else separates the points.
+                    previous_point_key = None;
+                }
+
+                element_stack.push(name.local_name.clone());
             }
-            XmlEvent::EndElement { name } => emitter.write(WriterEvent::EndElement {
-                name: Some(name.borrow()),
-            }),
-            XmlEvent::Characters(text) => emitter.write(WriterEvent::Characters(&text)),
-            XmlEvent::CData(text) => emitter.write(WriterEvent::CData(&text)),
-            XmlEvent::ProcessingInstruction { name, data } => {
-                emitter.write(WriterEvent::ProcessingInstruction {
-                    name: &name,
-                    data: data.as_deref(),
-                })
+            XmlEvent::Characters(text) if parent == Some("ele") => {
+                let is_point_elevation = element_stack
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .is_some_and(|element| POINT_ELEMENTS.contains(&element.as_str()));
+
+                if is_point_elevation {
+                    if let Some(decimals) = precision.elevation_decimals
+                        && let Ok(elevation) = text.trim().parse::<f64>()
+                    {
+                        *text = format_with_decimals(elevation, decimals);
+                    }
+                    point_key.2 = Some(text.trim().to_string());
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_stack.pop();
             }
-            XmlEvent::StartDocument {
-                version,
-                encoding,
-                standalone,
-            } => emitter.write(WriterEvent::StartDocument {
-                version,
-                encoding: Some(&encoding),
-                standalone,
-            }),
-            XmlEvent::EndDocument => Ok(()),
-            XmlEvent::Whitespace(_) => Ok(()),
-            XmlEvent::Comment(_) => Ok(()),
-            XmlEvent::Doctype { .. } => Ok(()),
-        };
-
-        result.with_context(|| GPX_SERIALIZE_ERROR)?;
+            _ => {}
+        }
+
+        let is_in_point = element_stack
+            .iter()
+            .any(|element| POLYLINE_POINT_ELEMENTS.contains(&element.as_str()));
+
+        if is_in_point {
+            point_events.push(event);
+            continue;
+        }
+
+        if !point_events.is_empty() {
+            // The event ends the current point.
+            point_events.push(event);
+
+            let is_duplicate =
+                precision.is_enabled() && previous_point_key.as_ref() == Some(&point_key);
+
+            if is_duplicate {
+                removed_count += 1;
+            } else {
+                for event in &point_events {
+                    write_event(&mut emitter, event).with_context(|| GPX_SERIALIZE_ERROR)?;
+                }
+                previous_point_key = Some(point_key.clone());
+            }
+
+            point_events.clear();
+            continue;
+        }
+
+        write_event(&mut emitter, &event).with_context(|| GPX_SERIALIZE_ERROR)?;
     }
 
     writer
         .flush()
         .with_context(|| error_messages::OUTPUT_FILE_WRITE_ERROR)?;
 
+    if precision.is_enabled() {
+        info!("Removed {removed_count} duplicate points after rounding.");
+    }
+
     Ok(())
 }
+
+fn round_coordinates(attributes: &mut [OwnedAttribute], precision: &PrecisionConfig) {
+    let Some(decimals) = precision.coordinate_decimals else {
+        return;
+    };
+
+    for attribute in attributes.iter_mut() {
+        let is_coordinate = matches!(attribute.name.local_name.as_str(), "lat" | "lon");
+
+        if is_coordinate && let Ok(value) = attribute.value.trim().parse::<f64>() {
+            attribute.value = format_with_decimals(value, decimals);
+        }
+    }
+}
+
+fn write_event<W: Write>(
+    emitter: &mut EventWriter<W>,
+    event: &XmlEvent,
+) -> xml::writer::Result<()> {
+    match event {
+        XmlEvent::StartElement {
+            name,
+            attributes,
+            namespace,
+        } => {
+            let cloned_attributes: Vec<Attribute> =
+                attributes.iter().map(OwnedAttribute::borrow).collect();
+            emitter.write(WriterEvent::StartElement {
+                name: name.borrow(),
+                attributes: Cow::Borrowed(cloned_attributes.as_slice()),
+                namespace: Cow::Borrowed(namespace),
+            })
+        }
+        XmlEvent::EndElement { name } => emitter.write(WriterEvent::EndElement {
+            name: Some(name.borrow()),
+        }),
+        XmlEvent::Characters(text) => emitter.write(WriterEvent::Characters(text)),
+        XmlEvent::CData(text) => emitter.write(WriterEvent::CData(text)),
+        XmlEvent::ProcessingInstruction { name, data } => {
+            emitter.write(WriterEvent::ProcessingInstruction {
+                name,
+                data: data.as_deref(),
+            })
+        }
+        XmlEvent::StartDocument {
+            version,
+            encoding,
+            standalone,
+        } => emitter.write(WriterEvent::StartDocument {
+            version: *version,
+            encoding: Some(encoding),
+            standalone: *standalone,
+        }),
+        XmlEvent::EndDocument => Ok(()),
+        XmlEvent::Whitespace(_) => Ok(()),
+        XmlEvent::Comment(_) => Ok(()),
+        XmlEvent::Doctype { .. } => Ok(()),
+    }
+}
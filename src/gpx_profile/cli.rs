@@ -0,0 +1,114 @@
+use super::renderer;
+use super::renderer::{
+    DEFAULT_PROFILE_HEIGHT, DEFAULT_PROFILE_WIDTH, DEFAULT_WAYPOINT_MAX_DISTANCE, ProfileConfig,
+    ProfileSource,
+};
+use crate::{error_messages, gpx_cli_util};
+use anyhow::{Context, anyhow};
+use clap::Parser;
+use log::info;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Input GPX file path.
+    input: PathBuf,
+
+    /// Output folder for the SVG files, or an SVG file path if the input has a single
+    /// track or route.
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+
+    /// Quiet: Disable logging.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Width of the image in pixels.
+    #[arg(long = "width", default_value_t = DEFAULT_PROFILE_WIDTH)]
+    width: f64,
+
+    /// Height of the image in pixels.
+    #[arg(long = "height", default_value_t = DEFAULT_PROFILE_HEIGHT)]
+    height: f64,
+
+    /// Maximum distance (in metres) of a waypoint from the line to be marked on the profile.
+    #[arg(long = "waypoint-distance", default_value_t = DEFAULT_WAYPOINT_MAX_DISTANCE)]
+    waypoint_max_distance: f64,
+
+    /// Do not mark waypoints on the profile.
+    #[arg(long = "no-waypoints")]
+    no_waypoints: bool,
+
+    /// Base file name for the resulting files. E.g. "<Base Name> Track 1.svg"
+    #[arg(long = "name")]
+    base_file_name: Option<String>,
+}
+
+pub fn run_cli() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_cli_with_args(args)
+}
+
+pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
+    crate::util::setup_logging(args.quiet);
+
+    let gpx = gpx_cli_util::read_input_gpx_file(&args.input)?;
+
+    let config = ProfileConfig {
+        width: args.width,
+        height: args.height,
+    };
+    let waypoint_max_distance = (!args.no_waypoints).then_some(args.waypoint_max_distance);
+
+    info!("Rendering elevation profiles...");
+    let profiles = renderer::render_gpx_profiles(&gpx, &config, waypoint_max_distance);
+
+    if !args.output.is_dir() && profiles.len() != 1 {
+        return Err(anyhow!(error_messages::OUTPUT_PATH_IS_NOT_FOLDER));
+    }
+
+    let base_file_name = match args.base_file_name {
+        Some(name) => name,
+        None => args
+            .input
+            .file_stem()
+            .with_context(|| error_messages::INPUT_PATH_MISSING_FILE_NAME)?
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    for profile in profiles {
+        let kind = match profile.source {
+            ProfileSource::Track => "Track",
+            ProfileSource::Route => "Route",
+        };
+        let name = profile
+            .name
+            .unwrap_or_else(|| format!("{kind} {}", profile.number));
+
+        let Some(svg) = profile.svg else {
+            info!(
+                "Skipping {} '{name}': No elevation data.",
+                kind.to_lowercase()
+            );
+            continue;
+        };
+
+        let output_path = if args.output.is_dir() {
+            args.output
+                .join(format!("{base_file_name} {kind} {}.svg", profile.number))
+        } else {
+            args.output.clone()
+        };
+
+        info!(
+            "Saving profile of {} '{name}' to '{}'...",
+            kind.to_lowercase(),
+            output_path.display()
+        );
+        fs::write(&output_path, svg).with_context(|| error_messages::OUTPUT_FILE_WRITE_ERROR)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,398 @@
+use crate::geo_util::{LocalProjection, closest_point_on_segment};
+use crate::gpx_info::info::get_polylines_of_track;
+use crate::gpx_info::stats::{ProfilePoint, compute_profile};
+use crate::svg_util::{
+    end_document, escape_xml, format_points, nice_tick_step, start_document, ticks_in_range,
+};
+use gpx::{Gpx, Waypoint};
+use std::fmt::Write;
+
+pub const DEFAULT_PROFILE_WIDTH: f64 = 800.0;
+pub const DEFAULT_PROFILE_HEIGHT: f64 = 300.0;
+pub const DEFAULT_WAYPOINT_MAX_DISTANCE: f64 = 50.0;
+
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const MARGIN_TOP: f64 = 50.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+const MIN_PLOT_SIZE: f64 = 50.0;
+
+const LINE_COLOUR: &str = "#2b6cb0";
+const FILL_COLOUR: &str = "#bee3f8";
+const GRID_COLOUR: &str = "#e2e8f0";
+const AXIS_COLOUR: &str = "#4a5568";
+const MARKER_COLOUR: &str = "#c53030";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileConfig {
+    /// Width of the image in pixels.
+    pub width: f64,
+    /// Height of the image in pixels.
+    pub height: f64,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProfileSource {
+    Track,
+    Route,
+}
+
+/// The rendered profile of a single track or route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderedProfile {
+    pub source: ProfileSource,
+    /// 1-based number of the track or route within the file.
+    pub number: usize,
+    pub name: Option<String>,
+    /// `None` if the track or route has no elevation data.
+    pub svg: Option<String>,
+}
+
+/// A waypoint that has been projected onto the profile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaypointMarker {
+    pub name: Option<String>,
+    pub distance_in_m: f64,
+}
+
+/// Renders a profile for each track and route of the file. If `waypoint_max_distance_in_m`
+/// is set, the waypoints of the file within that distance are marked on the profiles.
+pub fn render_gpx_profiles(
+    gpx: &Gpx,
+    config: &ProfileConfig,
+    waypoint_max_distance_in_m: Option<f64>,
+) -> Vec<RenderedProfile> {
+    let tracks = gpx.tracks.iter().map(|track| {
+        (
+            ProfileSource::Track,
+            &track.name,
+            get_polylines_of_track(track),
+        )
+    });
+    let routes = gpx.routes.iter().map(|route| {
+        (
+            ProfileSource::Route,
+            &route.name,
+            vec![route.points.as_slice()],
+        )
+    });
+
+    let mut track_count = 0;
+    let mut route_count = 0;
+
+    tracks
+        .chain(routes)
+        .map(|(source, name, polylines)| {
+            let counter = match source {
+                ProfileSource::Track => &mut track_count,
+                ProfileSource::Route => &mut route_count,
+            };
+            *counter += 1;
+
+            let profile = compute_profile(&polylines);
+            let markers = match waypoint_max_distance_in_m {
+                Some(max_distance) => {
+                    project_waypoints(&polylines, &profile, &gpx.waypoints, max_distance)
+                }
+                None => Vec::new(),
+            };
+
+            RenderedProfile {
+                source,
+                number: *counter,
+                name: name.clone(),
+                svg: render_profile_svg(name.as_deref(), &profile, &markers, config),
+            }
+        })
+        .collect()
+}
+
+/// Projects the waypoints onto the polylines and returns a marker for each waypoint that lies
+/// within `max_distance_in_m` of them. The polylines must be those the profile was computed from.
+pub fn project_waypoints(
+    polylines: &[&[Waypoint]],
+    profile: &[ProfilePoint],
+    waypoints: &[Waypoint],
+    max_distance_in_m: f64,
+) -> Vec<WaypointMarker> {
+    let all_points = polylines.iter().flat_map(|points| points.iter());
+    let Some(projection) = LocalProjection::centred_on(all_points.map(|point| point.point()))
+    else {
+        return Vec::new();
+    };
+
+    waypoints
+        .iter()
+        .filter_map(|waypoint| {
+            let position = projection.project(waypoint.point());
+            let mut closest: Option<(f64, f64)> = None;
+            let mut offset = 0;
+
+            for points in polylines {
+                let coords: Vec<_> = points
+                    .iter()
+                    .map(|point| projection.project(point.point()))
+                    .collect();
+
+                for i in 0..coords.len() {
+                    let next = if i + 1 < coords.len() { i + 1 } else { i };
+                    let (t, distance) = closest_point_on_segment(position, coords[i], coords[next]);
+
+                    if closest.is_none_or(|(_, closest_distance)| distance < closest_distance) {
+                        let from = profile[offset + i].distance_in_m;
+                        let to = profile[offset + next].distance_in_m;
+                        closest = Some((from + (to - from) * t, distance));
+                    }
+                }
+
+                offset += points.len();
+            }
+
+            let (distance_in_m, distance_to_line) = closest?;
+
+            (distance_to_line <= max_distance_in_m).then(|| WaypointMarker {
+                name: waypoint.name.clone(),
+                distance_in_m,
+            })
+        })
+        .collect()
+}
+
+/// Renders the elevation profile as an SVG document. Returns `None` if the profile has no
+/// elevation data.
+pub fn render_profile_svg(
+    title: Option<&str>,
+    profile: &[ProfilePoint],
+    markers: &[WaypointMarker],
+    config: &ProfileConfig,
+) -> Option<String> {
+    let points: Vec<(f64, f64)> = profile
+        .iter()
+        .filter_map(|point| Some((point.distance_in_m, point.elevation_in_m?)))
+        .collect();
+
+    let first = *points.first()?;
+    let last = *points.last()?;
+
+    let (min_index, min_point) = find_extreme(&points, |a, b| a < b);
+    let (max_index, max_point) = find_extreme(&points, |a, b| a > b);
+
+    let elevation_step = nice_tick_step((max_point.1 - min_point.1).max(10.0), 5);
+    let min_elevation = (min_point.1 / elevation_step).floor() * elevation_step;
+    let mut max_elevation = (max_point.1 / elevation_step).ceil() * elevation_step;
+    if max_elevation <= min_elevation {
+        max_elevation = min_elevation + elevation_step;
+    }
+
+    let start_distance = first.0;
+    let end_distance = last.0.max(start_distance + 1.0);
+
+    let plot_width = (config.width - MARGIN_LEFT - MARGIN_RIGHT).max(MIN_PLOT_SIZE);
+    let plot_height = (config.height - MARGIN_TOP - MARGIN_BOTTOM).max(MIN_PLOT_SIZE);
+    let width = MARGIN_LEFT + plot_width + MARGIN_RIGHT;
+    let height = MARGIN_TOP + plot_height + MARGIN_BOTTOM;
+    let bottom = MARGIN_TOP + plot_height;
+
+    let x = |distance: f64| {
+        MARGIN_LEFT + (distance - start_distance) / (end_distance - start_distance) * plot_width
+    };
+    let y = |elevation: f64| {
+        bottom - (elevation - min_elevation) / (max_elevation - min_elevation) * plot_height
+    };
+
+    let mut svg = start_document(width, height);
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    if let Some(title) = title {
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="24" font-size="16" font-weight="bold">{}</text>"#,
+            MARGIN_LEFT,
+            escape_xml(title)
+        )
+        .unwrap();
+    }
+
+    // Grid and elevation labels
+    for elevation in ticks_in_range(min_elevation, max_elevation, elevation_step) {
+        let line_y = y(elevation);
+        writeln!(
+            svg,
+            r#"<line x1="{MARGIN_LEFT:.1}" y1="{line_y:.1}" x2="{:.1}" y2="{line_y:.1}" stroke="{GRID_COLOUR}"/>"#,
+            MARGIN_LEFT + plot_width
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="end" fill="{AXIS_COLOUR}">{elevation:.0} m</text>"#,
+            MARGIN_LEFT - 6.0,
+            line_y + 4.0
+        )
+        .unwrap();
+    }
+
+    // Grid and distance ticks
+    let distance_step = nice_tick_step(end_distance - start_distance, 8);
+    for distance in ticks_in_range(start_distance, end_distance, distance_step) {
+        let line_x = x(distance);
+        writeln!(
+            svg,
+            r#"<line x1="{line_x:.1}" y1="{MARGIN_TOP:.1}" x2="{line_x:.1}" y2="{bottom:.1}" stroke="{GRID_COLOUR}"/>"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<line x1="{line_x:.1}" y1="{bottom:.1}" x2="{line_x:.1}" y2="{:.1}" stroke="{AXIS_COLOUR}"/>"#,
+            bottom + 5.0
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{line_x:.1}" y="{:.1}" font-size="11" text-anchor="middle" fill="{AXIS_COLOUR}">{}</text>"#,
+            bottom + 18.0,
+            format_distance(distance, distance_step)
+        )
+        .unwrap();
+    }
+
+    // Profile
+    let outline = points.iter().map(|&(d, e)| (x(d), y(e)));
+    let area = std::iter::once((x(first.0), bottom))
+        .chain(outline.clone())
+        .chain(std::iter::once((x(last.0), bottom)));
+    writeln!(
+        svg,
+        r#"<polygon points="{}" fill="{FILL_COLOUR}" stroke="none"/>"#,
+        format_points(area)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="{LINE_COLOUR}" stroke-width="2" stroke-linejoin="round"/>"#,
+        format_points(outline)
+    )
+    .unwrap();
+
+    // Axes
+    writeln!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="{AXIS_COLOUR}"/>"#,
+        format_points([
+            (MARGIN_LEFT, MARGIN_TOP),
+            (MARGIN_LEFT, bottom),
+            (MARGIN_LEFT + plot_width, bottom)
+        ])
+    )
+    .unwrap();
+
+    // Min / max labels
+    for (label, (distance, elevation), offset) in
+        [("Max", max_point, -8.0), ("Min", min_point, 16.0)]
+    {
+        if label == "Min" && min_index == max_index {
+            continue;
+        }
+
+        let (point_x, point_y) = (x(distance), y(elevation));
+        writeln!(
+            svg,
+            r#"<circle cx="{point_x:.1}" cy="{point_y:.1}" r="3" fill="{LINE_COLOUR}"/>"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{point_x:.1}" y="{:.1}" font-size="11" text-anchor="{}">{label} {elevation:.0} m</text>"#,
+            point_y + offset,
+            text_anchor_for(point_x, MARGIN_LEFT, plot_width)
+        )
+        .unwrap();
+    }
+
+    // Waypoint markers
+    for marker in markers {
+        let Some(elevation) = interpolate_elevation(&points, marker.distance_in_m) else {
+            continue;
+        };
+
+        let (marker_x, marker_y) = (x(marker.distance_in_m), y(elevation));
+        writeln!(
+            svg,
+            r##"<line x1="{marker_x:.1}" y1="{:.1}" x2="{marker_x:.1}" y2="{marker_y:.1}" stroke="{MARKER_COLOUR}" stroke-dasharray="3,3"/>"##,
+            MARGIN_TOP - 4.0
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<circle cx="{marker_x:.1}" cy="{marker_y:.1}" r="4" fill="{MARKER_COLOUR}" stroke="white"/>"#
+        )
+        .unwrap();
+
+        if let Some(name) = &marker.name {
+            writeln!(
+                svg,
+                r#"<text x="{marker_x:.1}" y="{:.1}" font-size="11" text-anchor="{}" fill="{MARKER_COLOUR}">{}</text>"#,
+                MARGIN_TOP - 8.0,
+                text_anchor_for(marker_x, MARGIN_LEFT, plot_width),
+                escape_xml(name)
+            )
+            .unwrap();
+        }
+    }
+
+    end_document(&mut svg);
+
+    Some(svg)
+}
+
+/// Returns the first point whose elevation is preferred over all others by `is_better`.
+fn find_extreme(points: &[(f64, f64)], is_better: fn(f64, f64) -> bool) -> (usize, (f64, f64)) {
+    let mut best = 0;
+    for (i, point) in points.iter().enumerate() {
+        if is_better(point.1, points[best].1) {
+            best = i;
+        }
+    }
+    (best, points[best])
+}
+
+fn interpolate_elevation(points: &[(f64, f64)], distance: f64) -> Option<f64> {
+    let i = points.partition_point(|point| point.0 < distance);
+
+    if i == 0 {
+        return points.first().map(|point| point.1);
+    }
+
+    let a = points[i - 1];
+    let Some(&b) = points.get(i) else {
+        return Some(a.1);
+    };
+
+    if b.0 <= a.0 {
+        return Some(b.1);
+    }
+
+    Some(a.1 + (b.1 - a.1) * (distance - a.0) / (b.0 - a.0))
+}
+
+/// Anchors labels near the edges of the plot so that they are not cut off.
+fn text_anchor_for(x: f64, plot_left: f64, plot_width: f64) -> &'static str {
+    let relative = (x - plot_left) / plot_width;
+
+    if relative < 0.1 {
+        "start"
+    } else if relative > 0.9 {
+        "end"
+    } else {
+        "middle"
+    }
+}
+
+fn format_distance(distance_in_m: f64, step_in_m: f64) -> String {
+    if step_in_m < 100.0 {
+        format!("{distance_in_m:.0} m")
+    } else if step_in_m < 1000.0 {
+        format!("{:.1} km", distance_in_m / 1000.0)
+    } else {
+        format!("{:.0} km", distance_in_m / 1000.0)
+    }
+}
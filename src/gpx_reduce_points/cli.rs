@@ -1,5 +1,5 @@
 use super::simplifier;
-use super::simplifier::{SimplificationMethod, SolverConfig};
+use super::simplifier::{PointBudget, SimplificationMethod, SolverConfig};
 use crate::{gpx_cli_util, util};
 use clap::{Parser, ValueEnum};
 use log::info;
@@ -16,6 +16,14 @@ enum AlgorithmOption {
     VW,
 }
 
+#[derive(ValueEnum, Clone, Copy, Eq, PartialEq, Debug)]
+enum BudgetOption {
+    /// Max point count for each track (and route)
+    PerItem,
+    /// Max point count for the whole file, distributed across the tracks (and routes)
+    File,
+}
+
 #[derive(Parser)]
 pub struct Args {
     /// Input GPX file
@@ -25,9 +33,28 @@ pub struct Args {
     #[arg(short = 'o', long = "output")]
     output: PathBuf,
 
-    /// Max point count per track
-    #[arg(short = 'n', long = "points")]
-    max_points: u32,
+    /// Max point count per track, or for the whole file (see --budget)
+    #[arg(short = 'n', long = "points", required_unless_present = "tolerance")]
+    max_points: Option<u32>,
+
+    /// Max deviation from the original line in metres. Instead of reducing the tracks to a
+    /// point count, removes points as long as the line stays within this distance
+    /// (Ramer-Douglas-Peucker)
+    #[arg(
+        short = 't',
+        long = "tolerance",
+        value_parser = simplifier::parse_tolerance,
+        conflicts_with_all = ["max_points", "budget", "max_iterations", "algorithm", "epsilon"]
+    )]
+    tolerance: Option<f64>,
+
+    /// What the max point count applies to
+    #[arg(short = 'b', long = "budget", default_value = "per-item")]
+    budget: BudgetOption,
+
+    /// Simplify routes as well as tracks
+    #[arg(short = 'r', long = "include-routes")]
+    include_routes: bool,
 
     /// Max solver iterations
     #[arg(short = 'i', long = "iterations", default_value = "20")]
@@ -54,27 +81,43 @@ pub fn run_cli() -> Result<(), anyhow::Error> {
 pub fn run_cli_with_args(args: Args) -> Result<(), anyhow::Error> {
     util::setup_logging(args.quiet);
 
-    gpx_cli_util::read_and_write_gpx_file(args.input, args.output, |gpx| {
+    gpx_cli_util::read_and_write_gpx_file(args.input.clone(), args.output.clone(), |gpx| {
         info!("Simplifying...");
-        let method = match args.algorithm {
-            AlgorithmOption::RDP => SimplificationMethod::RamerDouglasPeucker,
-            AlgorithmOption::VW => SimplificationMethod::VisvalingamWhyatt,
-        };
-
-        let initial_epsilon = args.epsilon.unwrap_or(match args.algorithm {
-            AlgorithmOption::RDP => DEFAULT_RDP_EPSILON,
-            AlgorithmOption::VW => DEFAULT_VW_EPSILON,
-        });
 
-        let solver_config = SolverConfig {
-            max_points: args.max_points,
-            max_iterations: args.max_iterations,
-            method,
-            initial_epsilon,
-        };
-
-        simplifier::simplify_all_tracks_in_gpx(gpx, &solver_config);
+        match (args.tolerance, args.max_points) {
+            (Some(tolerance), _) => {
+                simplifier::simplify_gpx_with_tolerance(gpx, tolerance, args.include_routes);
+            }
+            (None, Some(max_points)) => simplify_to_point_count(gpx, &args, max_points),
+            (None, None) => unreachable!("clap requires either --points or --tolerance"),
+        }
 
         Ok(())
     })
 }
+
+fn simplify_to_point_count(gpx: &mut gpx::Gpx, args: &Args, max_points: u32) {
+    let method = match args.algorithm {
+        AlgorithmOption::RDP => SimplificationMethod::RamerDouglasPeucker,
+        AlgorithmOption::VW => SimplificationMethod::VisvalingamWhyatt,
+    };
+
+    let initial_epsilon = args.epsilon.unwrap_or(match args.algorithm {
+        AlgorithmOption::RDP => DEFAULT_RDP_EPSILON,
+        AlgorithmOption::VW => DEFAULT_VW_EPSILON,
+    });
+
+    let solver_config = SolverConfig {
+        max_points,
+        max_iterations: args.max_iterations,
+        method,
+        initial_epsilon,
+    };
+
+    let budget = match args.budget {
+        BudgetOption::PerItem => PointBudget::PerItem,
+        BudgetOption::File => PointBudget::File,
+    };
+
+    simplifier::simplify_gpx(gpx, &solver_config, budget, args.include_routes);
+}
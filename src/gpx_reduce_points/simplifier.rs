@@ -1,3 +1,4 @@
+use crate::geo_util::distance_to_great_circle_segment;
 use geo;
 use geo::{LineString, Point, SimplifyIdx, SimplifyVwIdx};
 use log::info;
@@ -16,29 +17,183 @@ pub struct SolverConfig {
     pub initial_epsilon: f64,
 }
 
-pub fn simplify_all_tracks_in_gpx(gpx: &mut gpx::Gpx, solver_config: &SolverConfig) {
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PointBudget {
+    /// The max point count applies to each track and route separately.
+    PerItem,
+    /// The max point count applies to all tracks and routes in the file together.
+    File,
+}
+
+/// Simplifies the tracks, and optionally the routes, so that they stay within the point budget.
+/// For a file-wide budget, the same epsilon is used for all lines, so that the points are
+/// distributed according to the complexity of the lines rather than evenly.
+pub fn simplify_gpx(
+    gpx: &mut gpx::Gpx,
+    solver_config: &SolverConfig,
+    budget: PointBudget,
+    include_routes: bool,
+) {
     info!("Found {} track(s):", gpx.tracks.len());
+    if include_routes {
+        info!("Found {} route(s):", gpx.routes.len());
+    }
+
+    let routes: &mut [gpx::Route] = if include_routes {
+        &mut gpx.routes
+    } else {
+        &mut []
+    };
+
+    match budget {
+        PointBudget::PerItem => {
+            for track in gpx.tracks.iter_mut() {
+                let label = format!("track '{}'", track.name.as_deref().unwrap_or(""));
+                let mut lines: Vec<&mut Vec<gpx::Waypoint>> = track
+                    .segments
+                    .iter_mut()
+                    .map(|segment| &mut segment.points)
+                    .collect();
+                simplify_lines(&mut lines, &label, solver_config);
+            }
+
+            for route in routes.iter_mut() {
+                let label = format!("route '{}'", route.name.as_deref().unwrap_or(""));
+                simplify_lines(&mut [&mut route.points], &label, solver_config);
+            }
+        }
+        PointBudget::File => {
+            let label = if include_routes {
+                "all tracks and routes"
+            } else {
+                "all tracks"
+            };
+            let mut lines: Vec<&mut Vec<gpx::Waypoint>> = gpx
+                .tracks
+                .iter_mut()
+                .flat_map(|track| track.segments.iter_mut())
+                .map(|segment| &mut segment.points)
+                .chain(routes.iter_mut().map(|route| &mut route.points))
+                .collect();
+            simplify_lines(&mut lines, label, solver_config);
+        }
+    }
+}
 
-    for track in gpx.tracks.iter_mut() {
-        *track = simplify_track(track, solver_config);
+/// Parses the tolerance for simplification, which has to be a finite distance greater than 0.
+pub fn parse_tolerance(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(tolerance) if tolerance.is_finite() && tolerance > 0.0 => Ok(tolerance),
+        _ => Err(format!(
+            "invalid tolerance '{text}' (expected a distance in metres greater than 0)"
+        )),
     }
 }
 
-fn simplify_track(track: &gpx::Track, config: &SolverConfig) -> gpx::Track {
-    let point_count = count_points_in_track(track);
+/// Simplifies the tracks, and optionally the routes, with the Ramer-Douglas-Peucker algorithm,
+/// so that no removed point is more than `tolerance_in_m` away from the simplified line. The
+/// distances are measured on the sphere, so the tolerance holds at any latitude.
+pub fn simplify_gpx_with_tolerance(gpx: &mut gpx::Gpx, tolerance_in_m: f64, include_routes: bool) {
+    info!("Found {} track(s).", gpx.tracks.len());
+    if include_routes {
+        info!("Found {} route(s).", gpx.routes.len());
+    }
+
+    let routes: &mut [gpx::Route] = if include_routes {
+        &mut gpx.routes
+    } else {
+        &mut []
+    };
+
+    let lines = gpx
+        .tracks
+        .iter_mut()
+        .flat_map(|track| track.segments.iter_mut())
+        .map(|segment| &mut segment.points)
+        .chain(routes.iter_mut().map(|route| &mut route.points));
+
+    let mut original_count = 0;
+    let mut simplified_count = 0;
+
+    for points in lines {
+        original_count += points.len();
+
+        let positions: Vec<Point> = points.iter().map(|point| point.point()).collect();
+        let indices = simplify_points_with_tolerance(&positions, tolerance_in_m);
+
+        *points = indices.iter().map(|i| points[*i].clone()).collect();
+        simplified_count += points.len();
+    }
+
+    info!(
+        "Reduced {original_count} to {simplified_count} points with a tolerance of {tolerance_in_m} m."
+    );
+}
+
+/// Returns the indices of the points that are kept by the Ramer-Douglas-Peucker algorithm, so
+/// that each removed point is within the tolerance of the segment between its kept neighbours.
+fn simplify_points_with_tolerance(points: &[Point], tolerance_in_m: f64) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| {
+                let distance =
+                    distance_to_great_circle_segment(points[i], points[first], points[last]);
+                (i, distance)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, distance)) = farthest
+            && distance > tolerance_in_m
+        {
+            keep[i] = true;
+            ranges.push((first, i));
+            ranges.push((i, last));
+        }
+    }
+
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
 
-    let track_name: &str = track.name.as_ref().map_or("", |name| name.as_str());
+/// Simplifies the lines together, so that their total point count stays within the budget.
+fn simplify_lines(lines: &mut [&mut Vec<gpx::Waypoint>], label: &str, config: &SolverConfig) {
+    let point_count: u32 = lines.iter().map(|points| points.len() as u32).sum();
 
     if point_count <= config.max_points {
         info!(
-            "Track '{track_name}' already has {point_count} <= {} points.",
+            "No need to simplify {label}, which already has {point_count} <= {} points.",
             config.max_points
         );
-        return track.clone();
+        return;
     }
 
-    info!("Simplifying track '{track_name}' ({point_count} points)...");
+    info!("Simplifying {label} ({point_count} points)...");
+
+    let lines_as_line_strings: Vec<LineString> = lines
+        .iter()
+        .map(|points| get_line_string_from_points(points))
+        .collect();
+
+    let indices_per_line = find_simplified_indices(&lines_as_line_strings, config);
+
+    for (points, indices) in lines.iter_mut().zip(indices_per_line.iter()) {
+        **points = indices.iter().map(|i| points[*i].clone()).collect();
+    }
+}
 
+/// Returns for each line the indices of the points to keep to stay within the point budget.
+fn find_simplified_indices(
+    segments_as_lines: &[LineString],
+    config: &SolverConfig,
+) -> Vec<Vec<usize>> {
     // We perform a binary search to find an optimal epsilon value for simplification.
 
     // initial_epsilon = middle between min and max = (min + max) / 2
@@ -48,12 +203,6 @@ fn simplify_track(track: &gpx::Track, config: &SolverConfig) -> gpx::Track {
 
     let mut iteration_count: u32 = 0;
 
-    let segments_as_lines: Vec<LineString> = track
-        .segments
-        .iter()
-        .map(get_line_string_from_segment)
-        .collect();
-
     let mut best_segment_indices: Vec<Vec<usize>>;
     let mut best_point_count: u32;
 
@@ -63,7 +212,7 @@ fn simplify_track(track: &gpx::Track, config: &SolverConfig) -> gpx::Track {
         iteration_count += 1;
 
         best_segment_indices =
-            simplify_segment_lines(&segments_as_lines, max_epsilon, config.method);
+            simplify_segment_lines(segments_as_lines, max_epsilon, config.method);
         best_point_count = count_points_in_simplified_segment_indices(&best_segment_indices);
 
         info!("    [{iteration_count}] {best_point_count} points for epsilon={max_epsilon}");
@@ -83,7 +232,7 @@ fn simplify_track(track: &gpx::Track, config: &SolverConfig) -> gpx::Track {
 
         let epsilon = (max_epsilon + min_epsilon) / 2.0;
 
-        let simplified_indices = simplify_segment_lines(&segments_as_lines, epsilon, config.method);
+        let simplified_indices = simplify_segment_lines(segments_as_lines, epsilon, config.method);
         let new_point_count = count_points_in_simplified_segment_indices(&simplified_indices);
 
         info!("    [{iteration_count}] {new_point_count} points for epsilon={epsilon}.");
@@ -103,29 +252,19 @@ fn simplify_track(track: &gpx::Track, config: &SolverConfig) -> gpx::Track {
     }
 
     if best_point_count <= config.max_points {
-        info!("  Reduced track to {best_point_count} points.");
+        info!("  Reduced to {best_point_count} points.");
     } else {
         info!(
             "  Failed to reduce the point count sufficiently. Consider increasing the number of iterations."
         )
     }
 
-    let segments: Vec<gpx::TrackSegment> = track
-        .segments
-        .iter()
-        .zip(best_segment_indices.iter())
-        .map(|(segment, indices)| create_segment_from_indices(segment, indices))
-        .collect();
-
-    gpx::Track {
-        segments,
-        ..track.clone()
-    }
+    best_segment_indices
 }
 
 /// Returns for each segment the indices of the points that were kept.
 fn simplify_segment_lines(
-    segments_as_lines: &Vec<LineString>,
+    segments_as_lines: &[LineString],
     epsilon: f64,
     method: SimplificationMethod,
 ) -> Vec<Vec<usize>> {
@@ -135,12 +274,8 @@ fn simplify_segment_lines(
         .collect()
 }
 
-fn get_line_string_from_segment(segment: &gpx::TrackSegment) -> LineString {
-    let xy_points: Vec<Point> = segment
-        .points
-        .iter()
-        .map(|waypoint| waypoint.point())
-        .collect();
+fn get_line_string_from_points(points: &[gpx::Waypoint]) -> LineString {
+    let xy_points: Vec<Point> = points.iter().map(|waypoint| waypoint.point()).collect();
 
     LineString::from(xy_points)
 }
@@ -163,22 +298,3 @@ fn count_points_in_simplified_segment_indices(indices_per_segment: &Vec<Vec<usiz
         .map(|indices| indices.len() as u32)
         .sum()
 }
-
-fn create_segment_from_indices(
-    original: &gpx::TrackSegment,
-    indices: &Vec<usize>,
-) -> gpx::TrackSegment {
-    let points = indices
-        .iter()
-        .map(|i| original.points[*i].clone())
-        .collect();
-    gpx::TrackSegment { points }
-}
-
-fn count_points_in_track(track: &gpx::Track) -> u32 {
-    track
-        .segments
-        .iter()
-        .map(|segment| segment.points.len() as u32)
-        .sum()
-}
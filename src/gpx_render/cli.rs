@@ -0,0 +1,106 @@
+use super::renderer;
+use super::renderer::{
+    DEFAULT_COLOURS, DEFAULT_MAP_HEIGHT, DEFAULT_MAP_PADDING, DEFAULT_MAP_WIDTH,
+    DEFAULT_STROKE_WIDTH, MapConfig, MapProjection,
+};
+use crate::{error_messages, gpx_cli_util, util};
+use anyhow::{Context, anyhow};
+use clap::Parser;
+use log::info;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Input GPX file path.
+    input: PathBuf,
+
+    /// Output SVG file path or folder.
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+
+    /// Quiet: Disable logging.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Map projection.
+    #[arg(short = 'p', long = "projection", default_value = "mercator")]
+    projection: MapProjection,
+
+    /// Width of the image in pixels.
+    #[arg(long = "width", default_value_t = DEFAULT_MAP_WIDTH)]
+    width: f64,
+
+    /// Height of the image in pixels.
+    #[arg(long = "height", default_value_t = DEFAULT_MAP_HEIGHT)]
+    height: f64,
+
+    /// Space (in pixels) between the map features and the border of the image.
+    #[arg(long = "padding", default_value_t = DEFAULT_MAP_PADDING)]
+    padding: f64,
+
+    /// Comma-separated stroke colours of the tracks and routes, e.g. "red,#3182ce".
+    #[arg(long = "colours", value_delimiter = ',')]
+    colours: Vec<String>,
+
+    /// Stroke width of the tracks and routes in pixels.
+    #[arg(long = "stroke-width", default_value_t = DEFAULT_STROKE_WIDTH)]
+    stroke_width: f64,
+
+    /// Do not draw start and end markers.
+    #[arg(long = "no-start-end")]
+    no_start_end: bool,
+
+    /// Do not draw waypoints.
+    #[arg(long = "no-waypoints")]
+    no_waypoints: bool,
+
+    /// Do not label waypoints with their names.
+    #[arg(long = "no-labels")]
+    no_labels: bool,
+}
+
+pub fn run_cli() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_cli_with_args(args)
+}
+
+pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
+    util::setup_logging(args.quiet);
+
+    let gpx = gpx_cli_util::read_input_gpx_file(&args.input)?;
+
+    let colours = if args.colours.is_empty() {
+        DEFAULT_COLOURS
+            .iter()
+            .map(|colour| colour.to_string())
+            .collect()
+    } else {
+        args.colours
+    };
+
+    let config = MapConfig {
+        projection: args.projection,
+        width: args.width,
+        height: args.height,
+        padding: args.padding,
+        colours,
+        stroke_width: args.stroke_width,
+        start_end_markers: !args.no_start_end,
+        waypoints: !args.no_waypoints,
+        waypoint_labels: !args.no_labels,
+    };
+
+    info!("Rendering map...");
+    let svg = renderer::render_map_svg(&gpx, &config)
+        .ok_or_else(|| anyhow!(error_messages::NO_POINTS_TO_RENDER))?;
+
+    let output_is_folder = args.output.is_dir();
+    let mut output_path = util::process_output_path(args.output, &args.input)?;
+    if output_is_folder {
+        output_path.set_extension("svg");
+    }
+
+    info!("Writing output to {}...", output_path.display());
+    fs::write(&output_path, svg).with_context(|| error_messages::OUTPUT_FILE_WRITE_ERROR)
+}
@@ -0,0 +1,226 @@
+use crate::geo_util::LocalProjection;
+use crate::svg_util::{end_document, escape_xml, format_points, start_document};
+use clap::ValueEnum;
+use geo::{Coord, Point};
+use gpx::{Gpx, Waypoint};
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+pub const DEFAULT_MAP_WIDTH: f64 = 800.0;
+pub const DEFAULT_MAP_HEIGHT: f64 = 600.0;
+pub const DEFAULT_MAP_PADDING: f64 = 20.0;
+pub const DEFAULT_STROKE_WIDTH: f64 = 3.0;
+pub const DEFAULT_COLOURS: [&str; 6] = [
+    "#e53e3e", "#3182ce", "#38a169", "#d69e2e", "#805ad5", "#dd6b20",
+];
+
+const START_COLOUR: &str = "#2f855a";
+const END_COLOUR: &str = "#c53030";
+const WAYPOINT_COLOUR: &str = "#2d3748";
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MapProjection {
+    /// Web Mercator, as used by most online maps.
+    Mercator,
+    /// Equirectangular projection centred on the file. Preserves distances for small areas.
+    Local,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapConfig {
+    pub projection: MapProjection,
+    /// Width of the image in pixels.
+    pub width: f64,
+    /// Height of the image in pixels.
+    pub height: f64,
+    /// Minimum space (in pixels) between the features and the border of the image.
+    pub padding: f64,
+    /// Stroke colours of the tracks and routes. Used in turn if there are more lines than colours.
+    pub colours: Vec<String>,
+    pub stroke_width: f64,
+    /// Draw start and end markers on each track and route.
+    pub start_end_markers: bool,
+    pub waypoints: bool,
+    pub waypoint_labels: bool,
+}
+
+/// A track or route, in projected coordinates.
+struct MapLine {
+    polylines: Vec<Vec<Coord>>,
+    is_route: bool,
+}
+
+/// Renders the tracks, routes, and waypoints of the file as an SVG map.
+/// Returns `None` if the file does not contain any points.
+pub fn render_map_svg(gpx: &Gpx, config: &MapConfig) -> Option<String> {
+    let points = gpx
+        .tracks
+        .iter()
+        .flat_map(|track| track.segments.iter().flat_map(|segment| &segment.points))
+        .chain(gpx.routes.iter().flat_map(|route| &route.points))
+        .chain(gpx.waypoints.iter().filter(|_| config.waypoints))
+        .map(|point| point.point());
+
+    let local_projection = LocalProjection::centred_on(points.clone())?;
+    let project = |point: Point| match config.projection {
+        MapProjection::Mercator => project_web_mercator(point),
+        MapProjection::Local => local_projection.project(point),
+    };
+
+    let project_all = |points: &[Waypoint]| -> Vec<Coord> {
+        points.iter().map(|point| project(point.point())).collect()
+    };
+
+    let tracks = gpx.tracks.iter().map(|track| MapLine {
+        polylines: track
+            .segments
+            .iter()
+            .map(|segment| project_all(&segment.points))
+            .collect(),
+        is_route: false,
+    });
+    let routes = gpx.routes.iter().map(|route| MapLine {
+        polylines: vec![project_all(&route.points)],
+        is_route: true,
+    });
+    let lines: Vec<MapLine> = tracks.chain(routes).collect();
+
+    let fit = fit_to_image(points.map(project), config)?;
+
+    let mut svg = start_document(config.width, config.height);
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    for (i, line) in lines.iter().enumerate() {
+        let colour = config
+            .colours
+            .get(i % config.colours.len().max(1))
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_COLOURS[0]);
+        let dash = if line.is_route {
+            r#" stroke-dasharray="8,4""#
+        } else {
+            ""
+        };
+
+        for polyline in &line.polylines {
+            writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round"{dash}/>"#,
+                format_points(polyline.iter().map(|&coord| fit(coord))),
+                escape_xml(colour),
+                config.stroke_width
+            )
+            .unwrap();
+        }
+    }
+
+    if config.start_end_markers {
+        for line in &lines {
+            let first = line.polylines.iter().flat_map(|p| p.first()).next();
+            let last = line.polylines.iter().rev().flat_map(|p| p.last()).next();
+
+            for (coord, colour) in [(last, END_COLOUR), (first, START_COLOUR)] {
+                if let Some(&coord) = coord {
+                    let (x, y) = fit(coord);
+                    writeln!(
+                        svg,
+                        r#"<circle cx="{x:.1}" cy="{y:.1}" r="{:.1}" fill="{colour}" stroke="white" stroke-width="1.5"/>"#,
+                        config.stroke_width + 2.0
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    if config.waypoints {
+        for waypoint in &gpx.waypoints {
+            let (x, y) = fit(project(waypoint.point()));
+            writeln!(
+                svg,
+                r#"<circle cx="{x:.1}" cy="{y:.1}" r="4" fill="{WAYPOINT_COLOUR}" stroke="white"/>"#
+            )
+            .unwrap();
+
+            if let Some(name) = waypoint.name.as_ref().filter(|_| config.waypoint_labels) {
+                let anchor = if x > config.width * 0.75 {
+                    "end"
+                } else {
+                    "start"
+                };
+                let offset = if anchor == "end" { -7.0 } else { 7.0 };
+                writeln!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" font-size="12" text-anchor="{anchor}" fill="{WAYPOINT_COLOUR}" stroke="white" stroke-width="3" paint-order="stroke">{}</text>"#,
+                    x + offset,
+                    y + 4.0,
+                    escape_xml(name)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    end_document(&mut svg);
+
+    Some(svg)
+}
+
+/// Projects to Web Mercator, in metres.
+fn project_web_mercator(point: Point) -> Coord {
+    const EARTH_RADIUS_IN_M: f64 = 6_378_137.0;
+    const MAX_LATITUDE: f64 = 85.051_128_78;
+
+    let latitude = point.y().clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+
+    Coord {
+        x: EARTH_RADIUS_IN_M * point.x().to_radians(),
+        y: EARTH_RADIUS_IN_M * (PI / 4.0 + latitude / 2.0).tan().ln(),
+    }
+}
+
+/// Returns a function that scales projected coordinates to image coordinates, so that all
+/// of the given coordinates fit within the padded image, centred and with the aspect ratio kept.
+fn fit_to_image(
+    coords: impl Iterator<Item = Coord>,
+    config: &MapConfig,
+) -> Option<impl Fn(Coord) -> (f64, f64)> {
+    let (min, max) = coords.fold(None, |bounds: Option<(Coord, Coord)>, coord| {
+        Some(match bounds {
+            None => (coord, coord),
+            Some((min, max)) => (
+                Coord {
+                    x: min.x.min(coord.x),
+                    y: min.y.min(coord.y),
+                },
+                Coord {
+                    x: max.x.max(coord.x),
+                    y: max.y.max(coord.y),
+                },
+            ),
+        })
+    })?;
+
+    let available_width = (config.width - 2.0 * config.padding).max(1.0);
+    let available_height = (config.height - 2.0 * config.padding).max(1.0);
+
+    let content_width = max.x - min.x;
+    let content_height = max.y - min.y;
+
+    let scale = match (content_width > 0.0, content_height > 0.0) {
+        (true, true) => (available_width / content_width).min(available_height / content_height),
+        (true, false) => available_width / content_width,
+        (false, true) => available_height / content_height,
+        (false, false) => 1.0,
+    };
+
+    let offset_x = (config.width - content_width * scale) / 2.0;
+    let offset_y = (config.height - content_height * scale) / 2.0;
+
+    Some(move |coord: Coord| {
+        (
+            offset_x + (coord.x - min.x) * scale,
+            offset_y + (max.y - coord.y) * scale,
+        )
+    })
+}
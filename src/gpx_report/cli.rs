@@ -0,0 +1,121 @@
+use super::report;
+use super::report::{ReportConfig, ReportFormat};
+use crate::gpx_info::stats::{
+    DEFAULT_ELEVATION_THRESHOLD, DEFAULT_MOVING_SPEED_THRESHOLD, StatsConfig,
+};
+use crate::gpx_profile::renderer::{
+    DEFAULT_PROFILE_HEIGHT, DEFAULT_PROFILE_WIDTH, DEFAULT_WAYPOINT_MAX_DISTANCE, ProfileConfig,
+};
+use crate::gpx_render::renderer::{
+    DEFAULT_COLOURS, DEFAULT_MAP_PADDING, DEFAULT_STROKE_WIDTH, MapConfig, MapProjection,
+};
+use crate::{error_messages, util};
+use anyhow::Context;
+use clap::Parser;
+use log::info;
+use std::fs;
+use std::path::PathBuf;
+
+const MAP_WIDTH: f64 = 800.0;
+const MAP_HEIGHT: f64 = 500.0;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Input GPX file path.
+    input: PathBuf,
+
+    /// Output file path or folder.
+    #[arg(short = 'o', long = "output")]
+    output: PathBuf,
+
+    /// Quiet: Disable logging.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Report format. Defaults to Markdown for ".md" output files and HTML otherwise.
+    #[arg(short = 'f', long = "format")]
+    format: Option<ReportFormat>,
+
+    /// Map projection.
+    #[arg(short = 'p', long = "projection", default_value = "mercator")]
+    projection: MapProjection,
+
+    /// Maximum distance (in metres) of a waypoint from the line to be marked on the profile.
+    #[arg(long = "waypoint-distance", default_value_t = DEFAULT_WAYPOINT_MAX_DISTANCE)]
+    waypoint_max_distance: f64,
+}
+
+pub fn run_cli() -> anyhow::Result<()> {
+    let args = Args::parse();
+    run_cli_with_args(args)
+}
+
+pub fn run_cli_with_args(args: Args) -> anyhow::Result<()> {
+    util::setup_logging(args.quiet);
+
+    info!("Loading input file...");
+    let input_file_contents =
+        fs::read(&args.input).with_context(|| error_messages::INPUT_FILE_READ_ERROR)?;
+
+    info!("Parsing GPX file...");
+    let gpx = gpx::read(input_file_contents.as_slice())
+        .with_context(|| error_messages::GPX_PARSE_ERROR)?;
+
+    let format = args.format.unwrap_or_else(|| {
+        let is_markdown = args.output.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown")
+        });
+        if is_markdown {
+            ReportFormat::Markdown
+        } else {
+            ReportFormat::Html
+        }
+    });
+
+    let config = ReportConfig {
+        stats: StatsConfig {
+            elevation_threshold: DEFAULT_ELEVATION_THRESHOLD,
+            moving_speed_threshold: DEFAULT_MOVING_SPEED_THRESHOLD,
+            splits: None,
+            climbs: None,
+            include_profile: false,
+        },
+        map: MapConfig {
+            projection: args.projection,
+            width: MAP_WIDTH,
+            height: MAP_HEIGHT,
+            padding: DEFAULT_MAP_PADDING,
+            colours: DEFAULT_COLOURS.iter().map(|c| c.to_string()).collect(),
+            stroke_width: DEFAULT_STROKE_WIDTH,
+            start_end_markers: true,
+            waypoints: true,
+            waypoint_labels: true,
+        },
+        profile: ProfileConfig {
+            width: DEFAULT_PROFILE_WIDTH,
+            height: DEFAULT_PROFILE_HEIGHT,
+        },
+        waypoint_max_distance_in_m: args.waypoint_max_distance,
+    };
+
+    info!("Generating report...");
+    let report = report::generate_report(
+        &args.input,
+        input_file_contents.len(),
+        &gpx,
+        format,
+        &config,
+    )?;
+
+    let output_is_folder = args.output.is_dir();
+    let mut output_path = util::process_output_path(args.output, &args.input)?;
+    if output_is_folder {
+        output_path.set_extension(match format {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        });
+    }
+
+    info!("Writing output to {}...", output_path.display());
+    fs::write(&output_path, report).with_context(|| error_messages::OUTPUT_FILE_WRITE_ERROR)
+}
@@ -0,0 +1,518 @@
+use crate::gpx_info::info::{FileInfo, compute_gpx_info, summarize_file};
+use crate::gpx_info::output::format_duration;
+use crate::gpx_info::stats::{ElevationStats, StatsConfig, TimeStats};
+use crate::gpx_profile::renderer::{ProfileConfig, render_gpx_profiles};
+use crate::gpx_render::renderer::{MapConfig, render_map_svg};
+use crate::svg_util::escape_xml;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use clap::ValueEnum;
+use gpx::{Gpx, Link, Metadata};
+use std::fmt::Write;
+use std::path::Path;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ReportFormat {
+    /// Self-contained HTML page with inline SVG images.
+    Html,
+    /// Markdown document with the images embedded as data URIs.
+    Markdown,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReportConfig {
+    pub stats: StatsConfig,
+    pub map: MapConfig,
+    pub profile: ProfileConfig,
+    /// Maximum distance (in metres) of a waypoint from a track or route to be marked on its
+    /// elevation profile.
+    pub waypoint_max_distance_in_m: f64,
+}
+
+/// The contents of a report, independent of the output format.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub title: String,
+    pub metadata: Vec<(String, String)>,
+    pub links: Vec<Link>,
+    pub summary: Vec<(String, String)>,
+    pub lines: Table,
+    pub map_svg: Option<String>,
+    /// Title and SVG image of the elevation profile of each track and route.
+    pub profiles: Vec<(String, String)>,
+    pub waypoints: Table,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+pub fn generate_report(
+    path: &Path,
+    size_in_bytes: usize,
+    gpx: &Gpx,
+    format: ReportFormat,
+    config: &ReportConfig,
+) -> anyhow::Result<String> {
+    let report = build_report(path, size_in_bytes, gpx, config)?;
+
+    Ok(match format {
+        ReportFormat::Html => render_html(&report),
+        ReportFormat::Markdown => render_markdown(&report),
+    })
+}
+
+pub fn build_report(
+    path: &Path,
+    size_in_bytes: usize,
+    gpx: &Gpx,
+    config: &ReportConfig,
+) -> anyhow::Result<Report> {
+    let info = compute_gpx_info(path, size_in_bytes, gpx, &config.stats)?;
+    let metadata = gpx.metadata.as_ref();
+
+    let title = metadata
+        .and_then(|metadata| metadata.name.clone())
+        .unwrap_or_else(|| info.file_name.clone());
+
+    let profiles = render_gpx_profiles(
+        gpx,
+        &config.profile,
+        Some(config.waypoint_max_distance_in_m),
+    )
+    .into_iter()
+    .filter_map(|profile| Some((profile.name.unwrap_or_default(), profile.svg?)))
+    .collect();
+
+    Ok(Report {
+        title,
+        metadata: metadata.map(collect_metadata).unwrap_or_default(),
+        links: metadata
+            .map(|metadata| metadata.links.clone())
+            .unwrap_or_default(),
+        summary: collect_summary(&info),
+        lines: create_lines_table(&info),
+        map_svg: render_map_svg(gpx, &config.map),
+        profiles,
+        waypoints: create_waypoints_table(&info),
+    })
+}
+
+fn collect_metadata(metadata: &Metadata) -> Vec<(String, String)> {
+    let mut rows = Vec::new();
+
+    if let Some(description) = &metadata.description {
+        rows.push(("Description".to_string(), description.clone()));
+    }
+
+    if let Some(author) = &metadata.author {
+        let author = match (&author.name, &author.email) {
+            (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+            (Some(name), None) => Some(name.clone()),
+            (None, Some(email)) => Some(email.clone()),
+            (None, None) => None,
+        };
+        if let Some(author) = author {
+            rows.push(("Author".to_string(), author));
+        }
+    }
+
+    if let Some(time) = metadata.time {
+        let time = OffsetDateTime::from(time);
+        if let Ok(time) = time.format(&Rfc3339) {
+            rows.push(("Time".to_string(), time));
+        }
+    }
+
+    if let Some(keywords) = &metadata.keywords {
+        rows.push(("Keywords".to_string(), keywords.clone()));
+    }
+
+    if let Some(copyright) = &metadata.copyright {
+        let parts: Vec<String> = [
+            copyright.author.clone(),
+            copyright.year.map(|year| year.to_string()),
+            copyright.license.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !parts.is_empty() {
+            rows.push(("Copyright".to_string(), parts.join(", ")));
+        }
+    }
+
+    rows
+}
+
+fn collect_summary(info: &FileInfo) -> Vec<(String, String)> {
+    let summary = summarize_file(info);
+
+    let mut rows = vec![
+        ("File".to_string(), info.file_name.clone()),
+        ("Tracks".to_string(), summary.track_count.to_string()),
+        ("Routes".to_string(), summary.route_count.to_string()),
+        ("Waypoints".to_string(), summary.waypoint_count.to_string()),
+        (
+            "Distance".to_string(),
+            format_distance(summary.distance_in_m),
+        ),
+    ];
+
+    if summary.ascent_in_m > 0.0 || summary.descent_in_m > 0.0 {
+        rows.push(("Ascent".to_string(), format_elevation(summary.ascent_in_m)));
+        rows.push((
+            "Descent".to_string(),
+            format_elevation(summary.descent_in_m),
+        ));
+    }
+
+    if summary.duration_in_s > 0.0 {
+        rows.push((
+            "Duration".to_string(),
+            format_duration(summary.duration_in_s),
+        ));
+        rows.push((
+            "Moving time".to_string(),
+            format_duration(summary.moving_time_in_s),
+        ));
+    }
+
+    rows
+}
+
+fn create_lines_table(info: &FileInfo) -> Table {
+    let tracks = info.tracks.iter().map(|track| {
+        (
+            "Track",
+            &track.name,
+            track.distance_in_m,
+            &track.elevation,
+            &track.time,
+        )
+    });
+    let routes = info.routes.iter().map(|route| {
+        (
+            "Route",
+            &route.name,
+            route.distance_in_m,
+            &route.elevation,
+            &route.time,
+        )
+    });
+
+    Table {
+        headers: vec![
+            "Name",
+            "Type",
+            "Distance",
+            "Ascent",
+            "Descent",
+            "Min. elevation",
+            "Max. elevation",
+            "Duration",
+            "Moving time",
+        ],
+        rows: tracks
+            .chain(routes)
+            .map(|(kind, name, distance_in_m, elevation, time)| {
+                let elevation: Option<&ElevationStats> = elevation.as_ref();
+                let time: Option<&TimeStats> = time.as_ref();
+                vec![
+                    name.clone().unwrap_or_default(),
+                    kind.to_string(),
+                    format_distance(distance_in_m),
+                    format_optional(elevation.map(|e| format_elevation(e.ascent_in_m))),
+                    format_optional(elevation.map(|e| format_elevation(e.descent_in_m))),
+                    format_optional(elevation.map(|e| format_elevation(e.min_elevation_in_m))),
+                    format_optional(elevation.map(|e| format_elevation(e.max_elevation_in_m))),
+                    format_optional(time.map(|t| format_duration(t.duration_in_s))),
+                    format_optional(time.map(|t| format_duration(t.moving_time_in_s))),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn create_waypoints_table(info: &FileInfo) -> Table {
+    Table {
+        headers: vec!["Name", "Latitude", "Longitude", "Elevation", "Description"],
+        rows: info
+            .waypoints
+            .iter()
+            .map(|waypoint| {
+                vec![
+                    waypoint.name.clone().unwrap_or_default(),
+                    format!("{:.6}", waypoint.latitude),
+                    format!("{:.6}", waypoint.longitude),
+                    format_optional(waypoint.elevation_in_m.map(format_elevation)),
+                    waypoint.description.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+pub fn render_html(report: &Report) -> String {
+    let mut html = String::new();
+    let title = escape_xml(&report.title);
+
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, r#"<html lang="en">"#).unwrap();
+    writeln!(html, "<head>").unwrap();
+    writeln!(html, r#"<meta charset="utf-8">"#).unwrap();
+    writeln!(html, "<title>{title}</title>").unwrap();
+    writeln!(html, "<style>{HTML_STYLE}</style>").unwrap();
+    writeln!(html, "</head>").unwrap();
+    writeln!(html, "<body>").unwrap();
+    writeln!(html, "<h1>{title}</h1>").unwrap();
+
+    if !report.metadata.is_empty() || !report.links.is_empty() {
+        writeln!(html, "<h2>Metadata</h2>").unwrap();
+        write_html_key_value_table(&mut html, &report.metadata);
+
+        if !report.links.is_empty() {
+            writeln!(html, "<ul>").unwrap();
+            for link in &report.links {
+                let text = escape_xml(link.text.as_ref().unwrap_or(&link.href));
+                if is_safe_link(&link.href) {
+                    let href = escape_xml(&link.href);
+                    writeln!(html, r#"<li><a href="{href}">{text}</a></li>"#).unwrap();
+                } else {
+                    writeln!(html, "<li>{text}</li>").unwrap();
+                }
+            }
+            writeln!(html, "</ul>").unwrap();
+        }
+    }
+
+    writeln!(html, "<h2>Summary</h2>").unwrap();
+    write_html_key_value_table(&mut html, &report.summary);
+
+    if !report.lines.rows.is_empty() {
+        writeln!(html, "<h2>Tracks and Routes</h2>").unwrap();
+        write_html_table(&mut html, &report.lines);
+    }
+
+    if let Some(svg) = &report.map_svg {
+        writeln!(html, "<h2>Map</h2>").unwrap();
+        writeln!(html, r#"<figure>{svg}</figure>"#).unwrap();
+    }
+
+    if !report.profiles.is_empty() {
+        writeln!(html, "<h2>Elevation Profiles</h2>").unwrap();
+        for (_, svg) in &report.profiles {
+            writeln!(html, r#"<figure>{svg}</figure>"#).unwrap();
+        }
+    }
+
+    if !report.waypoints.rows.is_empty() {
+        writeln!(html, "<h2>Waypoints</h2>").unwrap();
+        write_html_table(&mut html, &report.waypoints);
+    }
+
+    writeln!(html, "</body>").unwrap();
+    writeln!(html, "</html>").unwrap();
+
+    html
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; max-width: 900px; margin: 2em auto; \
+    padding: 0 1em; color: #2d3748; } \
+    table { border-collapse: collapse; margin-bottom: 1em; } \
+    th, td { border: 1px solid #e2e8f0; padding: 4px 8px; text-align: left; } \
+    th { background: #f7fafc; } \
+    figure { margin: 0 0 1em 0; } \
+    svg { max-width: 100%; height: auto; }";
+
+fn write_html_key_value_table(html: &mut String, rows: &[(String, String)]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    writeln!(html, "<table>").unwrap();
+    for (key, value) in rows {
+        writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape_xml(key),
+            escape_xml(value)
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+}
+
+fn write_html_table(html: &mut String, table: &Table) {
+    writeln!(html, "<table>").unwrap();
+
+    let headers: String = table
+        .headers
+        .iter()
+        .map(|header| format!("<th>{}</th>", escape_xml(header)))
+        .collect();
+    writeln!(html, "<tr>{headers}</tr>").unwrap();
+
+    for row in &table.rows {
+        let cells: String = row
+            .iter()
+            .map(|cell| format!("<td>{}</td>", escape_xml(cell)))
+            .collect();
+        writeln!(html, "<tr>{cells}</tr>").unwrap();
+    }
+
+    writeln!(html, "</table>").unwrap();
+}
+
+pub fn render_markdown(report: &Report) -> String {
+    let mut markdown = String::new();
+
+    writeln!(markdown, "# {}\n", escape_markdown(&report.title)).unwrap();
+
+    if !report.metadata.is_empty() || !report.links.is_empty() {
+        writeln!(markdown, "## Metadata\n").unwrap();
+        write_markdown_key_value_table(&mut markdown, &report.metadata);
+
+        if !report.links.is_empty() {
+            for link in &report.links {
+                let text = escape_markdown(link.text.as_ref().unwrap_or(&link.href));
+                if is_safe_link(&link.href) {
+                    let href = encode_markdown_link_target(&link.href);
+                    writeln!(markdown, "- [{text}](<{href}>)").unwrap();
+                } else {
+                    writeln!(markdown, "- {text}").unwrap();
+                }
+            }
+            writeln!(markdown).unwrap();
+        }
+    }
+
+    writeln!(markdown, "## Summary\n").unwrap();
+    write_markdown_key_value_table(&mut markdown, &report.summary);
+
+    if !report.lines.rows.is_empty() {
+        writeln!(markdown, "## Tracks and Routes\n").unwrap();
+        write_markdown_table(&mut markdown, &report.lines);
+    }
+
+    if let Some(svg) = &report.map_svg {
+        writeln!(markdown, "## Map\n").unwrap();
+        writeln!(markdown, "![Map]({})\n", svg_data_uri(svg)).unwrap();
+    }
+
+    if !report.profiles.is_empty() {
+        writeln!(markdown, "## Elevation Profiles\n").unwrap();
+        for (name, svg) in &report.profiles {
+            let alt = if name.is_empty() {
+                "Elevation profile".to_string()
+            } else {
+                format!("Elevation profile of {}", escape_markdown(name))
+            };
+            writeln!(markdown, "![{alt}]({})\n", svg_data_uri(svg)).unwrap();
+        }
+    }
+
+    if !report.waypoints.rows.is_empty() {
+        writeln!(markdown, "## Waypoints\n").unwrap();
+        write_markdown_table(&mut markdown, &report.waypoints);
+    }
+
+    markdown
+}
+
+fn write_markdown_key_value_table(markdown: &mut String, rows: &[(String, String)]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    writeln!(markdown, "| | |\n| --- | --- |").unwrap();
+    for (key, value) in rows {
+        writeln!(
+            markdown,
+            "| **{}** | {} |",
+            escape_markdown(key),
+            escape_markdown_cell(value)
+        )
+        .unwrap();
+    }
+
+    writeln!(markdown).unwrap();
+}
+
+fn write_markdown_table(markdown: &mut String, table: &Table) {
+    let headers: Vec<String> = table
+        .headers
+        .iter()
+        .map(|header| escape_markdown(header))
+        .collect();
+    writeln!(markdown, "| {} |", headers.join(" | ")).unwrap();
+    writeln!(markdown, "|{}", " --- |".repeat(headers.len())).unwrap();
+
+    for row in &table.rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown_cell(cell)).collect();
+        writeln!(markdown, "| {} |", cells.join(" | ")).unwrap();
+    }
+
+    writeln!(markdown).unwrap();
+}
+
+/// Escapes the characters that would otherwise be interpreted as Markdown formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Escapes a table cell, which must not contain line breaks.
+fn escape_markdown_cell(text: &str) -> String {
+    escape_markdown(text).replace(['\r', '\n'], " ")
+}
+
+/// Only links with these schemes are made clickable, so that e.g. "javascript:" links in the
+/// input file cannot run scripts in the report.
+fn is_safe_link(href: &str) -> bool {
+    let href = href.trim_start().to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| href.starts_with(scheme))
+}
+
+/// Percent-encodes the characters that would end a Markdown link target in angle brackets.
+fn encode_markdown_link_target(href: &str) -> String {
+    href.trim()
+        .replace(' ', "%20")
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn svg_data_uri(svg: &str) -> String {
+    format!("data:image/svg+xml;base64,{}", BASE64.encode(svg))
+}
+
+fn format_optional(text: Option<String>) -> String {
+    text.unwrap_or_else(|| "-".to_string())
+}
+
+fn format_distance(distance_in_m: f64) -> String {
+    format!("{:.2} km", distance_in_m / 1000.0)
+}
+
+fn format_elevation(elevation_in_m: f64) -> String {
+    format!("{elevation_in_m:.0} m")
+}
@@ -1,6 +1,15 @@
 pub mod gpx_clean {
     pub mod cleaner;
     pub mod cli;
+    pub mod duplicates;
+    pub mod metadata;
+    pub mod outliers;
+    pub mod pauses;
+    pub mod precision;
+    pub mod privacy;
+    pub mod renaming;
+    pub mod times;
+    pub mod transliteration;
 }
 
 pub mod gpx_reduce_points {
@@ -49,11 +58,46 @@ pub mod kml_to_gpx {
 }
 
 pub mod gpx_info {
+    pub mod chart;
     pub mod cli;
+    pub mod climbs;
     pub mod info;
+    pub mod output;
+    pub mod splits;
+    pub mod stats;
+}
+
+pub mod gpx_profile {
+    pub mod cli;
+    pub mod renderer;
+}
+
+pub mod gpx_render {
+    pub mod cli;
+    pub mod renderer;
+}
+
+pub mod gpx_report {
+    pub mod cli;
+    pub mod report;
+}
+
+pub mod gpx_diff {
+    pub mod cli;
+    pub mod differ;
+    pub mod output;
+}
+
+pub mod gpx_elevation {
+    pub mod cli;
+    pub mod dem;
+    pub mod elevation;
+    pub mod geoid;
 }
 
 pub mod util;
 
 pub mod error_messages;
+pub mod geo_util;
 mod gpx_cli_util;
+pub mod svg_util;
@@ -1,16 +1,17 @@
 use clap::{Parser, Subcommand};
 use gpx_tools::{
-    gpx_clean, gpx_info, gpx_merge_files, gpx_merge_tracks, gpx_minify, gpx_reduce_points,
-    gpx_reverse_tracks, gpx_routes_to_tracks, gpx_split_file, gpx_to_kml, kml_to_gpx,
+    gpx_clean, gpx_diff, gpx_elevation, gpx_info, gpx_merge_files, gpx_merge_tracks, gpx_minify,
+    gpx_profile, gpx_reduce_points, gpx_render, gpx_report, gpx_reverse_tracks,
+    gpx_routes_to_tracks, gpx_split_file, gpx_to_kml, kml_to_gpx,
 };
 use std::error::Error;
 
 #[derive(Subcommand)]
 enum Command {
     /// Fix encoding errors, remove metadata and features, change track names.
-    Clean(gpx_clean::cli::Args),
+    Clean(Box<gpx_clean::cli::Args>),
 
-    /// Reduce the number of points in tracks.
+    /// Reduce the number of points in tracks and routes.
     ReducePoints(gpx_reduce_points::cli::Args),
 
     /// Convert a GPX file to KML format.
@@ -39,6 +40,21 @@ enum Command {
 
     /// Print summary stats and metadata from GPX tracks, routes, and waypoints.
     Info(gpx_info::cli::Args),
+
+    /// Draw SVG elevation profiles of tracks and routes.
+    Profile(gpx_profile::cli::Args),
+
+    /// Draw tracks, routes, and waypoints on an SVG map.
+    Render(gpx_render::cli::Args),
+
+    /// Create an HTML or Markdown report with stats, map, and elevation profiles.
+    Report(gpx_report::cli::Args),
+
+    /// Compare two GPX files and report the differences.
+    Diff(gpx_diff::cli::Args),
+
+    /// Set elevations from local elevation models and convert them with geoid grids.
+    Elevation(gpx_elevation::cli::Args),
 }
 
 /// A collection of command-line tools for working with GPX files.
@@ -52,7 +68,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let args = Cli::parse();
 
     match args.command {
-        Command::Clean(args) => gpx_clean::cli::run_cli_with_args(args)?,
+        Command::Clean(args) => gpx_clean::cli::run_cli_with_args(*args)?,
         Command::ReducePoints(args) => gpx_reduce_points::cli::run_cli_with_args(args)?,
         Command::GpxToKml(args) => gpx_to_kml::cli::run_cli_with_args(args)?,
         Command::KmlToGpx(args) => kml_to_gpx::cli::run_cli_with_args(args)?,
@@ -63,6 +79,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         Command::Minify(args) => gpx_minify::cli::run_cli_with_args(args)?,
         Command::SplitFile(args) => gpx_split_file::cli::run_cli_with_args(args)?,
         Command::Info(args) => gpx_info::cli::run_cli_with_args(args)?,
+        Command::Profile(args) => gpx_profile::cli::run_cli_with_args(args)?,
+        Command::Render(args) => gpx_render::cli::run_cli_with_args(args)?,
+        Command::Report(args) => gpx_report::cli::run_cli_with_args(args)?,
+        Command::Diff(args) => gpx_diff::cli::run_cli_with_args(args)?,
+        Command::Elevation(args) => gpx_elevation::cli::run_cli_with_args(args)?,
     }
 
     Ok(())
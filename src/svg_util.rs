@@ -0,0 +1,76 @@
+use std::fmt::Write;
+
+// Helper functions for generating SVG documents.
+
+/// Escapes the characters that have a special meaning in XML text and attribute values.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Returns a "nice" step size (1, 2 or 5 times a power of ten) that divides the range
+/// into roughly `target_count` intervals.
+pub fn nice_tick_step(range: f64, target_count: usize) -> f64 {
+    if range <= 0.0 || target_count == 0 {
+        return 1.0;
+    }
+
+    let rough_step = range / target_count as f64;
+    let magnitude = 10f64.powf(rough_step.log10().floor());
+    let normalised = rough_step / magnitude;
+
+    let nice = if normalised < 1.5 {
+        1.0
+    } else if normalised < 3.5 {
+        2.0
+    } else if normalised < 7.5 {
+        5.0
+    } else {
+        10.0
+    };
+
+    nice * magnitude
+}
+
+/// Returns the multiples of `step` within `[min, max]`.
+pub fn ticks_in_range(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Formats a list of coordinates as the value of a `points` attribute.
+pub fn format_points(points: impl IntoIterator<Item = (f64, f64)>) -> String {
+    let mut text = String::new();
+
+    for (x, y) in points {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        write!(text, "{x:.1},{y:.1}").unwrap();
+    }
+
+    text
+}
+
+pub fn start_document(width: f64, height: f64) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif">"#
+    ) + "\n"
+}
+
+pub fn end_document(svg: &mut String) {
+    svg.push_str("</svg>\n");
+}